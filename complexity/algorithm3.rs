@@ -0,0 +1,19 @@
+use super::*;
+
+// Numbers in no particular order, the same ones every time
+pub fn input(n: usize) -> Vec<i64> {
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    (0..n)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as i64
+        })
+        .collect()
+}
+
+pub fn run(mut input: Vec<i64>) -> Vec<i64> {
+    sort(&mut input);
+    input
+}
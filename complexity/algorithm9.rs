@@ -0,0 +1,23 @@
+use super::*;
+
+// Numbers in no particular order, the same ones every time
+pub fn input(n: usize) -> Vec<i64> {
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    (0..n)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as i64 >> 8
+        })
+        .collect()
+}
+
+// Add every number to a heap, then take them all out again
+pub fn run(input: Vec<i64>) -> i64 {
+    let mut heap = MinHeap::new();
+    for value in input {
+        heap.add(value);
+    }
+    heap.fold(0, |sum: i64, value| sum.wrapping_add(value))
+}
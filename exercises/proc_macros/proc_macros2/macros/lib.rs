@@ -0,0 +1,52 @@
+// The `FieldNames` derive macro of proc_macros2.
+
+// I AM NOT DONE
+
+use proc_macro::{Delimiter, Spacing, TokenStream, TokenTree};
+
+#[proc_macro_derive(FieldNames)]
+pub fn derive_field_names(input: TokenStream) -> TokenStream {
+    let mut name = None;
+    let mut fields = None;
+    let mut tokens = input.into_iter();
+    while let Some(token) = tokens.next() {
+        match token {
+            TokenTree::Ident(ident) if ident.to_string() == "struct" => {
+                name = tokens.next().map(|name| name.to_string());
+            }
+            // The fields of the struct are in the group delimited by braces
+            TokenTree::Group(group) if group.delimiter() == Delimiter::Brace => {
+                fields = Some(field_names(group.stream()));
+            }
+            _ => {}
+        }
+    }
+    let (Some(name), Some(fields)) = (name, fields) else {
+        panic!("FieldNames can only be derived for structs with named fields");
+    };
+    let fields: Vec<String> = fields.iter().map(|field| format!("{field:?}")).collect();
+    format!(
+        "impl {name} {{ fn field_names() -> Vec<&'static str> {{ vec![{}] }} }}",
+        fields.join(", ")
+    )
+    .parse()
+    .unwrap()
+}
+
+// The names of the fields declared by `fields`, the tokens between the
+// braces of a struct
+fn field_names(fields: TokenStream) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut previous = None;
+    for token in fields {
+        if let TokenTree::Punct(punct) = &token {
+            if punct.as_char() == ':' && punct.spacing() == Spacing::Alone {
+                if let Some(TokenTree::Ident(name)) = &previous {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        previous = Some(token);
+    }
+    names
+}
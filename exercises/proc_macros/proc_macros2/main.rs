@@ -0,0 +1,48 @@
+// proc_macros2.rs
+//
+// Derive macros can look inside the item they are derived for. The
+// `FieldNames` derive macro in the `macros` directory gives a struct a
+// `field_names` function listing the names of its fields, but it can't find
+// the fields yet. Help it find them!
+//
+// Execute `rustlings hint proc_macros2` or use the `hint` watch subcommand for
+// a hint.
+
+// I AM NOT DONE
+
+use macros::FieldNames;
+
+#[allow(dead_code)]
+#[derive(FieldNames)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[allow(dead_code)]
+#[derive(FieldNames)]
+pub struct Recipe {
+    pub name: std::string::String,
+    ingredients: Vec<(String, u32)>,
+    /// How long it takes to cook, if anyone timed it
+    minutes: Option<u64>,
+}
+
+fn main() {
+    println!("{:?}", Point::field_names());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_fields() {
+        assert_eq!(Point::field_names(), vec!["x", "y"]);
+    }
+
+    #[test]
+    fn skips_paths_and_attributes() {
+        assert_eq!(Recipe::field_names(), vec!["name", "ingredients", "minutes"]);
+    }
+}
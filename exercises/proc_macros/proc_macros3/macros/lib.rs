@@ -0,0 +1,25 @@
+// The `log_calls` attribute macro of proc_macros3.
+
+// I AM NOT DONE
+
+use proc_macro::{Delimiter, Group, TokenStream, TokenTree};
+
+// An attribute macro gets the tokens of the attribute's arguments and those
+// of the item it is on, and returns the tokens that replace the item.
+#[proc_macro_attribute]
+pub fn log_calls(_args: TokenStream, item: TokenStream) -> TokenStream {
+    let mut tokens: Vec<TokenTree> = item.into_iter().collect();
+    let name = tokens
+        .iter()
+        .skip_while(|token| !matches!(token, TokenTree::Ident(ident) if ident.to_string() == "fn"))
+        .nth(1)
+        .expect("log_calls can only be put on functions")
+        .to_string();
+    let Some(TokenTree::Group(body)) = tokens.pop() else {
+        panic!("log_calls can only be put on functions with a body");
+    };
+    let mut logged: TokenStream = format!("crate::log_call({name:?});").parse().unwrap();
+    logged.extend(body.stream());
+    tokens.push(TokenTree::Group(Group::new(Delimiter::Brace, logged)));
+    tokens.into_iter().collect()
+}
@@ -0,0 +1,15 @@
+use super::*;
+
+#[test]
+fn sorts_like_the_standard_library() {
+    check(
+        |rng| rng.vec(50, |rng| rng.i32(-1000, 1000)),
+        |input: &Vec<i32>| {
+            let mut sorted = input.clone();
+            sort(&mut sorted);
+            let mut expected = input.clone();
+            expected.sort();
+            sorted == expected
+        },
+    );
+}
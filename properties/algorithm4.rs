@@ -0,0 +1,25 @@
+use super::*;
+use std::collections::BTreeSet;
+
+#[test]
+fn finds_exactly_the_inserted_values() {
+    check(
+        |rng| {
+            let inserted = rng.vec(40, |rng| rng.i32(-50, 50));
+            let searched = rng.vec(20, |rng| rng.i32(-50, 50));
+            (inserted, searched)
+        },
+        |(inserted, searched): &(Vec<i32>, Vec<i32>)| {
+            let mut tree = BinarySearchTree::new();
+            let mut expected = BTreeSet::new();
+            for &value in inserted {
+                tree.insert(value);
+                expected.insert(value);
+            }
+            searched
+                .iter()
+                .chain(inserted)
+                .all(|&value| tree.search(value) == expected.contains(&value))
+        },
+    );
+}
@@ -0,0 +1,26 @@
+use super::*;
+
+// Every operation either adds a value (true) or takes the smallest one (false)
+#[test]
+fn takes_values_in_increasing_order() {
+    check(
+        |rng| rng.vec(60, |rng| (rng.bool(), rng.i32(-100, 100))),
+        |operations: &Vec<(bool, i32)>| {
+            let mut heap = MinHeap::new();
+            let mut expected: Vec<i32> = Vec::new();
+            for &(add, value) in operations {
+                if add {
+                    heap.add(value);
+                    expected.push(value);
+                    expected.sort_by(|a, b| b.cmp(a));
+                } else if heap.next() != expected.pop() {
+                    return false;
+                }
+                if heap.len() != expected.len() {
+                    return false;
+                }
+            }
+            true
+        },
+    );
+}
@@ -0,0 +1,114 @@
+// Allocation tracking for rustlings exercises.
+// This file is compiled into test exercises that track their allocations.
+// Its allocator counts the allocations of every thread, so that each test
+// can check that it freed everything it allocated, and it catches memory
+// that is freed twice.
+#![allow(dead_code)]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::io::Write;
+
+// Written in front of every allocation, to tell whether it was freed
+const LIVE: usize = 0x11fe_a110_c8ed_0001;
+const FREED: usize = 0xf8ee_d0a1_10c8_0002;
+// The space in front of every allocation holding the marker
+const HEADER: usize = 16;
+
+thread_local! {
+    // How many allocations made by this thread are still live, and their size
+    static ALLOCATIONS: Cell<isize> = const { Cell::new(0) };
+    static BYTES: Cell<isize> = const { Cell::new(0) };
+    // The test running on this thread
+    static TEST: Cell<&'static str> = const { Cell::new("") };
+}
+
+struct Tracking;
+
+#[global_allocator]
+static ALLOCATOR: Tracking = Tracking;
+
+impl Tracking {
+    // The layout of an allocation along with its header, and where the
+    // allocation starts within it
+    fn with_header(layout: Layout) -> (Layout, usize) {
+        let offset = HEADER.max(layout.align());
+        let layout = Layout::from_size_align(layout.size() + offset, offset)
+            .expect("allocation too large to track");
+        (layout, offset)
+    }
+}
+
+unsafe impl GlobalAlloc for Tracking {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let (tracked, offset) = Self::with_header(layout);
+        let base = System.alloc(tracked);
+        if base.is_null() {
+            return base;
+        }
+        let ptr = base.add(offset);
+        ptr.cast::<usize>().sub(1).write(LIVE);
+        count(1, layout.size() as isize);
+        ptr
+    }
+
+    // Freed memory is never given back, so that its marker stays readable
+    // and a second free is noticed. Exercises are small enough for that.
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let marker = ptr.cast::<usize>().sub(1);
+        if marker.read() != LIVE {
+            let test = TEST.try_with(Cell::get).unwrap_or("");
+            let mut stderr = std::io::stderr();
+            let _ = write!(stderr, "rustlings: memory at {ptr:p} was freed twice");
+            if !test.is_empty() {
+                let _ = write!(stderr, " in test {test}");
+            }
+            let _ = writeln!(stderr, "!");
+            std::process::abort();
+        }
+        marker.write(FREED);
+        count(-1, -(layout.size() as isize));
+    }
+}
+
+fn count(allocations: isize, bytes: isize) {
+    let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + allocations));
+    let _ = BYTES.try_with(|count| count.set(count.get() + bytes));
+}
+
+// Checks that a test frees everything it allocates on its thread.
+// rustlings creates one at the start of every test.
+pub struct Guard {
+    name: &'static str,
+    allocations: isize,
+    bytes: isize,
+}
+
+impl Guard {
+    pub fn new(name: &'static str) -> Guard {
+        TEST.with(|test| test.set(name));
+        Guard {
+            name,
+            allocations: ALLOCATIONS.with(Cell::get),
+            bytes: BYTES.with(Cell::get),
+        }
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        TEST.with(|test| test.set(""));
+        // A failing test already failed
+        if std::thread::panicking() {
+            return;
+        }
+        let allocations = ALLOCATIONS.with(Cell::get) - self.allocations;
+        let bytes = BYTES.with(Cell::get) - self.bytes;
+        if allocations > 0 {
+            panic!(
+                "{} leaked memory: {allocations} allocation(s) of {bytes} bytes in total were never freed",
+                self.name
+            );
+        }
+    }
+}
@@ -0,0 +1,64 @@
+// The allocator counting allocations and catching double frees
+const HARNESS: &str = include_str!("allocation_harness.rs");
+// The module the harness is compiled into
+const HARNESS_MODULE: &str = "rustlings_allocations";
+
+// The module to add to a test exercise to track its allocations
+pub fn module() -> String {
+    format!("#[cfg(test)]\nmod {HARNESS_MODULE} {{\n{HARNESS}\n}}\n")
+}
+
+// Make every test of the given source check that it frees everything it
+// allocates. The check is added on the line that opens the test function,
+// so that line numbers in compiler errors stay the same.
+pub fn instrument(source: &str) -> String {
+    let mut instrumented = String::with_capacity(source.len());
+    let mut test = false;
+    let mut name = None;
+    for line in source.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("#[test]") {
+            test = true;
+        } else if test && name.is_none() {
+            name = test_name(trimmed);
+        }
+        match (name, line.find('{')) {
+            (Some(test_name), Some(brace)) => {
+                instrumented.push_str(&line[..=brace]);
+                instrumented.push_str(&format!(
+                    " let _rustlings_allocations = crate::{HARNESS_MODULE}::Guard::new(\"{test_name}\");"
+                ));
+                instrumented.push_str(&line[brace + 1..]);
+                test = false;
+                name = None;
+            }
+            _ => instrumented.push_str(line),
+        }
+    }
+    instrumented
+}
+
+// The name of the function declared on the given line, if it declares one
+fn test_name(line: &str) -> Option<&str> {
+    let (_, declaration) = line.split_once("fn ")?;
+    let end = declaration
+        .find(|c: char| !c.is_alphanumeric() && c != '_')
+        .unwrap_or(declaration.len());
+    Some(&declaration[..end]).filter(|name| !name.is_empty())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_instrument_tests() {
+        let source = "fn add(a: i32) -> i32 {\n    a\n}\n\n#[cfg(test)]\nmod tests {\n    #[test]\n    #[should_panic]\n    fn adds()\n    {\n        add(1);\n    }\n}\n";
+        let instrumented = instrument(source);
+        assert_eq!(instrumented.lines().count(), source.lines().count());
+        assert!(instrumented.starts_with("fn add(a: i32) -> i32 {\n"));
+        assert!(instrumented.contains(
+            "    { let _rustlings_allocations = crate::rustlings_allocations::Guard::new(\"adds\");\n"
+        ));
+    }
+}
@@ -0,0 +1,290 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display, Formatter};
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+// The input sizes an exercise is measured at, unless info.toml says otherwise
+const DEFAULT_SIZES: &[usize] = &[1000, 2000, 4000, 8000, 16000];
+// How much faster than the bound the running time may grow, as an exponent
+// of n. This absorbs measurement noise, while an O(n²) solution measured
+// against `n log n` still exceeds it by close to 1.
+const TOLERANCE: f64 = 0.5;
+// The module the benchmark is compiled into
+pub const BENCH_MODULE: &str = "rustlings_bench";
+// The test measuring the exercise, within BENCH_MODULE
+pub const BENCH_TEST: &str = "rustlings_measure";
+// How the benchmark prints the time taken for an input size
+const TIMING_PREFIX: &str = "rustlings-bench ";
+
+// Exercises are graded while holding this lock for reading, and a
+// benchmark runs while holding it for writing, so that it has the machine
+// to itself even when exercises are graded in parallel
+pub static BENCH_LOCK: RwLock<()> = RwLock::new(());
+
+// How to measure an exercise, as configured in info.toml
+#[derive(Deserialize, Debug)]
+pub struct Bench {
+    // A file defining `input(n)`, which makes an input of size n, and
+    // `run(input)`, which passes it to the exercise
+    pub file: PathBuf,
+    // How fast the running time may grow, like `n log n`
+    pub bound: Complexity,
+    // The input sizes to measure at
+    #[serde(default)]
+    pub sizes: Option<Vec<usize>>,
+}
+
+// A bound on how fast the running time grows with the input size n
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+pub enum Complexity {
+    Constant,
+    Logarithmic,
+    Linear,
+    Linearithmic,
+    Quadratic,
+    Cubic,
+}
+
+impl Complexity {
+    fn growth(self, n: f64) -> f64 {
+        match self {
+            Complexity::Constant => 1.0,
+            Complexity::Logarithmic => n.ln(),
+            Complexity::Linear => n,
+            Complexity::Linearithmic => n * n.ln(),
+            Complexity::Quadratic => n * n,
+            Complexity::Cubic => n * n * n,
+        }
+    }
+}
+
+impl TryFrom<String> for Complexity {
+    type Error = String;
+
+    fn try_from(bound: String) -> Result<Complexity, String> {
+        match bound.split_whitespace().collect::<Vec<_>>().join(" ").as_str() {
+            "1" => Ok(Complexity::Constant),
+            "log n" => Ok(Complexity::Logarithmic),
+            "n" => Ok(Complexity::Linear),
+            "n log n" => Ok(Complexity::Linearithmic),
+            "n^2" => Ok(Complexity::Quadratic),
+            "n^3" => Ok(Complexity::Cubic),
+            _ => Err(format!(
+                "unknown bound `{bound}`, expected one of `1`, `log n`, `n`, `n log n`, `n^2` or `n^3`"
+            )),
+        }
+    }
+}
+
+impl From<Complexity> for String {
+    fn from(bound: Complexity) -> String {
+        bound.to_string()
+    }
+}
+
+impl Display for Complexity {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Complexity::Constant => "1",
+            Complexity::Logarithmic => "log n",
+            Complexity::Linear => "n",
+            Complexity::Linearithmic => "n log n",
+            Complexity::Quadratic => "n^2",
+            Complexity::Cubic => "n^3",
+        })
+    }
+}
+
+// The time the exercise took for one input size
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct Timing {
+    pub n: usize,
+    pub nanos: u64,
+}
+
+// The measured running times of an exercise and how fast they grow
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Benchmark {
+    pub bound: Complexity,
+    pub timings: Vec<Timing>,
+    // The running time grows like n to the power of this exponent
+    pub exponent: f64,
+    pub within_bound: bool,
+}
+
+impl Benchmark {
+    // Fit the growth rate of the timings and compare it with the bound.
+    // The running time is within the bound if divided by the bound, it
+    // doesn't grow noticeably anymore.
+    pub fn fit(bound: Complexity, timings: Vec<Timing>) -> Benchmark {
+        let exponent = slope(&timings, |_| 1.0);
+        let excess = slope(&timings, |n| bound.growth(n));
+        Benchmark {
+            bound,
+            timings,
+            exponent,
+            within_bound: excess <= TOLERANCE,
+        }
+    }
+
+    // How fast the running time grows, compared with the bound
+    pub fn growth(&self) -> String {
+        format!(
+            "the running time grows like n^{:.2}, the bound is {}",
+            self.exponent, self.bound
+        )
+    }
+}
+
+// The timings, one input size per line
+impl Display for Benchmark {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for timing in &self.timings {
+            writeln!(
+                f,
+                "n = {:>8}: {:>10.3} ms",
+                timing.n,
+                timing.nanos as f64 / 1e6
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// The slope of the timings divided by `growth` on a log-log scale,
+// fitted with least squares
+fn slope(timings: &[Timing], growth: impl Fn(f64) -> f64) -> f64 {
+    let points: Vec<(f64, f64)> = timings
+        .iter()
+        .map(|timing| {
+            let n = timing.n as f64;
+            // A timing of zero would have no logarithm
+            let nanos = timing.nanos.max(1) as f64;
+            (n.ln(), (nanos / growth(n)).ln())
+        })
+        .collect();
+    if points.len() < 2 {
+        return 0.0;
+    }
+    let count = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / count;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / count;
+    let covariance: f64 = points
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum();
+    let variance: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    if variance == 0.0 {
+        return 0.0;
+    }
+    covariance / variance
+}
+
+// The module to add to an exercise to measure it with the given benchmark.
+// For every size, it prints the fastest of several runs, so that a run
+// slowed down by something else doesn't count.
+pub fn module(bench: &str, sizes: Option<&[usize]>) -> String {
+    let sizes = sizes.unwrap_or(DEFAULT_SIZES);
+    format!(
+        r#"#[cfg(test)]
+mod {BENCH_MODULE} {{
+{bench}
+
+#[test]
+fn {BENCH_TEST}() {{
+    for n in {sizes:?} {{
+        let mut fastest = u128::MAX;
+        let mut spent = std::time::Duration::ZERO;
+        let mut runs = 0;
+        while runs < 3 || (spent.as_millis() < 20 && runs < 100) {{
+            let input = input(n);
+            let start = std::time::Instant::now();
+            std::hint::black_box(run(std::hint::black_box(input)));
+            let elapsed = start.elapsed();
+            fastest = fastest.min(elapsed.as_nanos());
+            spent += elapsed;
+            runs += 1;
+        }}
+        println!("{TIMING_PREFIX}{{n}} {{fastest}}");
+    }}
+}}
+}}
+"#
+    )
+}
+
+// The timings printed by the benchmark.
+// The first one shares its line with the name of the test.
+pub fn parse(output: &str) -> Vec<Timing> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (_, timing) = line.split_once(TIMING_PREFIX)?;
+            let (n, nanos) = timing.split_once(' ')?;
+            Some(Timing {
+                n: n.parse().ok()?,
+                nanos: nanos.trim().parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn timings(f: impl Fn(f64) -> f64) -> Vec<Timing> {
+        DEFAULT_SIZES
+            .iter()
+            .map(|&n| Timing {
+                n,
+                nanos: f(n as f64) as u64,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_bounds() {
+        assert_eq!(
+            Complexity::try_from("n  log n".to_string()),
+            Ok(Complexity::Linearithmic)
+        );
+        assert_eq!(
+            Complexity::try_from("n^2".to_string()),
+            Ok(Complexity::Quadratic)
+        );
+        assert!(Complexity::try_from("n!".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_fit_growth_rate() {
+        let quadratic = Benchmark::fit(Complexity::Linearithmic, timings(|n| 3.0 * n * n));
+        assert!((quadratic.exponent - 2.0).abs() < 0.01);
+        assert!(!quadratic.within_bound);
+
+        let linearithmic = Benchmark::fit(Complexity::Linearithmic, timings(|n| 20.0 * n * n.ln()));
+        assert!(linearithmic.within_bound);
+        // Faster than the bound is fine
+        let linear = Benchmark::fit(Complexity::Linearithmic, timings(|n| 5.0 * n));
+        assert!(linear.within_bound);
+    }
+
+    #[test]
+    fn test_parse_timings() {
+        let output = "running 1 test\ntest rustlings_bench::rustlings_measure ... rustlings-bench 1000 52311\nrustlings-bench 2000 104000\nok\n";
+        assert_eq!(
+            parse(output),
+            [
+                Timing {
+                    n: 1000,
+                    nanos: 52311
+                },
+                Timing {
+                    n: 2000,
+                    nanos: 104000
+                }
+            ]
+        );
+    }
+}
@@ -0,0 +1,164 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+// The directory of the course holding the crates exercises depend on, as
+// created by `cargo vendor`
+pub const VENDOR_DIR: &str = "vendor";
+
+// How to build a cargo exercise, as configured in info.toml
+#[derive(Deserialize, Clone, Debug, Default)]
+pub struct CargoProject {
+    // The cargo subcommand that grades the exercise
+    #[serde(default)]
+    pub command: CargoCommand,
+    // A file to generate the Cargo.toml from instead of the default one.
+    // `{name}`, `{root}`, `{build_script}`, `{edition}` and `{dependencies}`
+    // in it are replaced with the name of the exercise, the path of its
+    // crate root, that of its build script, its edition and the section
+    // declaring its dependencies.
+    #[serde(default)]
+    pub manifest: Option<PathBuf>,
+    // The build script of the package
+    #[serde(default)]
+    pub build_script: Option<PathBuf>,
+    // The features to enable, which the manifest must declare
+    #[serde(default)]
+    pub features: Vec<String>,
+}
+
+// A cargo subcommand grading an exercise
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CargoCommand {
+    // Build the package and run its tests
+    #[default]
+    Test,
+    // Build the package and run its binary
+    Run,
+    // Lint the package with clippy, denying all warnings, and run its
+    // binary like `run`
+    Clippy,
+    // Only build the package
+    Build,
+}
+
+impl CargoProject {
+    // The arguments to cargo enabling the features
+    pub fn feature_args(&self) -> Vec<String> {
+        if self.features.is_empty() {
+            return Vec::new();
+        }
+        vec!["--features".to_string(), self.features.join(",")]
+    }
+}
+
+impl CargoCommand {
+    // The arguments to cargo that build the package, reporting diagnostics
+    pub fn compile_args(self) -> &'static [&'static str] {
+        match self {
+            CargoCommand::Test => &["test", "--no-run"],
+            CargoCommand::Run | CargoCommand::Build => &["build"],
+            CargoCommand::Clippy => &["clippy"],
+        }
+    }
+
+    // The arguments to cargo that build the package after `compile_args`
+    // passed, if those only check it. Running the package then doesn't
+    // build it within the limits of the exercise.
+    pub fn build_args(self) -> Option<&'static [&'static str]> {
+        match self {
+            CargoCommand::Clippy => Some(&["build"]),
+            _ => None,
+        }
+    }
+
+    // The arguments to pass to the compiler or clippy after `--`
+    pub fn lint_args(self) -> &'static [&'static str] {
+        match self {
+            CargoCommand::Clippy => &["--", "-D", "warnings", "-D", "clippy::float_cmp"],
+            _ => &[],
+        }
+    }
+
+    // The arguments to cargo that run the built package, if it runs
+    pub fn run_args(self) -> Option<&'static [&'static str]> {
+        match self {
+            CargoCommand::Test => Some(&["test", "--quiet"]),
+            CargoCommand::Run | CargoCommand::Clippy => Some(&["run", "--quiet"]),
+            CargoCommand::Build => None,
+        }
+    }
+
+    // The arguments to the program cargo runs, after `--`
+    pub fn program_args(self) -> &'static [&'static str] {
+        match self {
+            CargoCommand::Test => &["--", "--show-output"],
+            _ => &[],
+        }
+    }
+}
+
+// The arguments making cargo work without network access, resolving
+// crates from the vendored ones if the course has any
+pub fn offline_args() -> Vec<String> {
+    let mut args = vec!["--offline".to_string()];
+    if let Ok(vendor) = Path::new(VENDOR_DIR).canonicalize() {
+        let vendor = vendor.display().to_string().replace('\\', "/");
+        args.extend([
+            "--config".to_string(),
+            r#"source.crates-io.replace-with="vendored-sources""#.to_string(),
+            "--config".to_string(),
+            format!(r#"source.vendored-sources.directory="{vendor}""#),
+        ]);
+    }
+    args
+}
+
+// The `[dependencies]` section declaring the given crates, which are
+// declared like in a Cargo.toml
+pub fn dependencies_toml(dependencies: &BTreeMap<String, toml::Value>) -> String {
+    let dependencies = dependencies.clone().into_iter().collect();
+    let mut manifest = toml::value::Table::new();
+    manifest.insert("dependencies".to_string(), toml::Value::Table(dependencies));
+    toml::Value::Table(manifest).to_string()
+}
+
+// The executable built last, according to cargo's JSON messages
+pub fn executable(messages: &str) -> Option<PathBuf> {
+    messages
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter(|message| message["reason"] == "compiler-artifact")
+        .filter_map(|message| message["executable"].as_str().map(PathBuf::from))
+        .next_back()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_dependencies_toml() {
+        let dependencies: BTreeMap<String, toml::Value> = toml::from_str(
+            "rand = \"0.8\"\nserde = { version = \"1.0\", features = [\"derive\"] }\nsha2 = \"0.10\"",
+        )
+        .unwrap();
+        let section = dependencies_toml(&dependencies);
+        let parsed: toml::Value = toml::from_str(&section).unwrap();
+        assert_eq!(parsed["dependencies"]["rand"].as_str(), Some("0.8"));
+        assert_eq!(parsed["dependencies"]["serde"]["features"][0].as_str(), Some("derive"));
+        assert_eq!(parsed["dependencies"]["sha2"].as_str(), Some("0.10"));
+    }
+
+    #[test]
+    fn test_find_executable() {
+        let messages = r#"{"reason":"compiler-artifact","target":{"name":"build-script-build"},"executable":null}
+{"reason":"compiler-artifact","target":{"name":"exercise"},"executable":"/tmp/target/debug/exercise"}
+{"reason":"build-finished","success":true}"#;
+        assert_eq!(
+            executable(messages),
+            Some(PathBuf::from("/tmp/target/debug/exercise"))
+        );
+    }
+}
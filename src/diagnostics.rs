@@ -0,0 +1,239 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt::Write;
+
+// Makes rustc emit one JSON diagnostic per line, with the usual
+// human readable rendering (including colors) embedded in it
+pub const RUSTC_JSON_ARGS: &[&str] = &["--error-format=json", "--json=diagnostic-rendered-ansi"];
+// Makes cargo emit one JSON message per line on stdout
+pub const CARGO_JSON_ARGS: &[&str] = &["--message-format=json-diagnostic-rendered-ansi"];
+
+// The severity of a diagnostic
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Level {
+    #[serde(rename = "error: internal compiler error")]
+    InternalCompilerError,
+    Error,
+    Warning,
+    Note,
+    Help,
+    FailureNote,
+    #[serde(other)]
+    Other,
+}
+
+// The error code or lint name of a diagnostic
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Code {
+    // The code itself, like `E0382` or `clippy::float_cmp`
+    pub code: String,
+    // The text `rustc --explain` prints for the code, if there is one
+    #[serde(skip_serializing)]
+    pub explanation: Option<String>,
+}
+
+// A source location a diagnostic points at
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Span {
+    pub file_name: String,
+    pub line_start: usize,
+    pub line_end: usize,
+    pub column_start: usize,
+    pub column_end: usize,
+    // Whether this is the main location of the diagnostic
+    pub is_primary: bool,
+    pub label: Option<String>,
+    // The code suggested to replace this span with, if any
+    pub suggested_replacement: Option<String>,
+}
+
+// A single diagnostic as emitted by rustc (see `--error-format=json`)
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub code: Option<Code>,
+    pub level: Level,
+    pub spans: Vec<Span>,
+    // Notes, help messages and suggestions attached to this diagnostic
+    pub children: Vec<Diagnostic>,
+    // The human readable rendering of the diagnostic
+    #[serde(skip_serializing)]
+    pub rendered: Option<String>,
+}
+
+// A message emitted by cargo with `--message-format=json`.
+// Only compiler messages are of interest here.
+#[derive(Deserialize)]
+struct CargoMessage {
+    reason: String,
+    message: Option<Diagnostic>,
+}
+
+impl Diagnostic {
+    // Whether this is one of the trailing notes like "aborting due to
+    // 2 previous errors", which only summarize the other diagnostics
+    pub fn is_summary(&self) -> bool {
+        self.spans.is_empty()
+            && self.code.is_none()
+            && (self.level == Level::FailureNote
+                || self.message.starts_with("aborting due to")
+                || self.message.ends_with("emitted"))
+    }
+
+    pub fn is_error(&self) -> bool {
+        matches!(self.level, Level::Error | Level::InternalCompilerError) && !self.is_summary()
+    }
+
+    pub fn is_warning(&self) -> bool {
+        self.level == Level::Warning && !self.is_summary()
+    }
+
+    // The first paragraph of the `rustc --explain` text for this diagnostic
+    pub fn explanation_summary(&self) -> Option<String> {
+        let explanation = self.code.as_ref()?.explanation.as_ref()?;
+        let paragraph = explanation
+            .split("\n\n")
+            .find(|paragraph| !paragraph.trim().is_empty())?;
+        Some(paragraph.split_whitespace().collect::<Vec<_>>().join(" "))
+    }
+}
+
+// Split compiler output into the diagnostics it contains and the remaining
+// lines, which are returned unchanged.
+// This handles both rustc's JSON diagnostics and cargo's JSON messages.
+pub fn parse(output: &str) -> (Vec<Diagnostic>, String) {
+    let mut diagnostics = Vec::new();
+    let mut rest = String::new();
+    for line in output.lines() {
+        if line.starts_with('{') {
+            if let Ok(message) = serde_json::from_str::<CargoMessage>(line) {
+                if message.reason == "compiler-message" {
+                    diagnostics.extend(message.message);
+                }
+                continue;
+            }
+            if let Ok(diagnostic) = serde_json::from_str::<Diagnostic>(line) {
+                diagnostics.push(diagnostic);
+                continue;
+            }
+        }
+        rest.push_str(line);
+        rest.push('\n');
+    }
+    (diagnostics, rest)
+}
+
+// The distinct codes of the errors among the given diagnostics, sorted
+pub fn error_codes(diagnostics: &[Diagnostic]) -> Vec<String> {
+    let mut codes: Vec<String> = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.is_error())
+        .filter_map(|diagnostic| Some(diagnostic.code.as_ref()?.code.clone()))
+        .collect();
+    codes.sort();
+    codes.dedup();
+    codes
+}
+
+// Render diagnostics the way rustc would, followed by a short explanation
+// of every error code the first time it shows up and the number of errors
+// and warnings
+pub fn render(diagnostics: &[Diagnostic]) -> String {
+    let mut rendered = String::new();
+    let mut explained = HashSet::new();
+    for diagnostic in diagnostics.iter().filter(|d| !d.is_summary()) {
+        match &diagnostic.rendered {
+            Some(text) => rendered.push_str(text),
+            None => {
+                let _ = writeln!(rendered, "{:?}: {}", diagnostic.level, diagnostic.message);
+            }
+        }
+        if let (Some(code), Some(summary)) = (&diagnostic.code, diagnostic.explanation_summary()) {
+            if explained.insert(code.code.clone()) {
+                let _ = writeln!(
+                    rendered,
+                    "{} {}: {}\n",
+                    console::style("explain").cyan().bold(),
+                    console::style(&code.code).bold(),
+                    summary
+                );
+            }
+        }
+    }
+
+    let errors = diagnostics.iter().filter(|d| d.is_error()).count();
+    let warnings = diagnostics.iter().filter(|d| d.is_warning()).count();
+    if errors + warnings > 0 {
+        let _ = writeln!(
+            rendered,
+            "{}, {}",
+            plural(errors, "error"),
+            plural(warnings, "warning")
+        );
+    }
+    rendered
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("{count} {noun}")
+    } else {
+        format!("{count} {noun}s")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MOVED_VALUE: &str = r#"{"$message_type":"diagnostic","message":"borrow of moved value: `s`","code":{"code":"E0382","explanation":"A variable was used after its contents have been moved elsewhere.\n\nErroneous code example:\n"},"level":"error","spans":[{"file_name":"e.rs","byte_start":49,"byte_end":50,"line_start":1,"line_end":1,"column_start":50,"column_end":51,"is_primary":true,"text":[],"label":"value borrowed here after move","suggested_replacement":null,"suggestion_applicability":null,"expansion":null}],"children":[{"message":"consider cloning the value","code":null,"level":"help","spans":[{"file_name":"e.rs","byte_start":32,"byte_end":32,"line_start":1,"line_end":1,"column_start":33,"column_end":33,"is_primary":true,"text":[],"label":null,"suggested_replacement":".clone()","suggestion_applicability":"MachineApplicable","expansion":null}],"children":[],"rendered":null}],"rendered":"error[E0382]: borrow of moved value: `s`\n"}"#;
+    const ABORTING: &str = r#"{"$message_type":"diagnostic","message":"aborting due to 1 previous error","code":null,"level":"error","spans":[],"children":[],"rendered":"error: aborting due to 1 previous error\n\n"}"#;
+
+    #[test]
+    fn test_parse_rustc_diagnostics() {
+        let output = format!("{MOVED_VALUE}\nnote: some linker output\n{ABORTING}\n");
+        let (diagnostics, rest) = parse(&output);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(rest, "note: some linker output\n");
+
+        let moved = &diagnostics[0];
+        assert!(moved.is_error());
+        assert_eq!(moved.code.as_ref().unwrap().code, "E0382");
+        assert_eq!(moved.spans[0].line_start, 1);
+        assert_eq!(
+            moved.children[0].spans[0].suggested_replacement.as_deref(),
+            Some(".clone()")
+        );
+        assert_eq!(
+            moved.explanation_summary().unwrap(),
+            "A variable was used after its contents have been moved elsewhere."
+        );
+        assert!(diagnostics[1].is_summary());
+    }
+
+    #[test]
+    fn test_parse_cargo_messages() {
+        let output = format!(
+            "{{\"reason\":\"compiler-artifact\",\"package_id\":\"x\"}}\n{{\"reason\":\"compiler-message\",\"package_id\":\"x\",\"message\":{MOVED_VALUE}}}\nrunning 1 test\n"
+        );
+        let (diagnostics, rest) = parse(&output);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(rest, "running 1 test\n");
+    }
+
+    #[test]
+    fn test_render_counts_and_explains() {
+        let (diagnostics, _) = parse(&format!("{MOVED_VALUE}\n{MOVED_VALUE}\n{ABORTING}"));
+        let rendered = console::strip_ansi_codes(&render(&diagnostics)).to_string();
+        assert_eq!(rendered.matches("explain E0382").count(), 1);
+        assert!(!rendered.contains("aborting due to"));
+        assert!(rendered.ends_with("2 errors, 0 warnings\n"));
+    }
+
+    #[test]
+    fn test_error_codes() {
+        let (diagnostics, _) = parse(&format!("{MOVED_VALUE}\n{MOVED_VALUE}\n{ABORTING}"));
+        assert_eq!(error_codes(&diagnostics), ["E0382"]);
+    }
+}
@@ -0,0 +1,178 @@
+use crate::exercise::Exercise;
+use crate::pristine;
+use console::style;
+use similar::{ChangeTag, TextDiff};
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// How the learner changed an exercise compared to its original version
+pub struct Changes {
+    // The unified diff from the original version to the current one
+    pub unified: String,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+impl Changes {
+    // Compare the exercise with its original version, file by file
+    pub fn of(exercise: &Exercise) -> io::Result<Changes> {
+        let mut changes = Changes {
+            unified: String::new(),
+            insertions: 0,
+            deletions: 0,
+        };
+        for file in exercise.files() {
+            let path = file.display();
+            // A file the learner added is compared with an empty one
+            let (original, original_name) = if pristine::added(exercise, &file) {
+                (String::new(), "/dev/null".to_string())
+            } else {
+                let original = pristine::original(&file)?;
+                (
+                    String::from_utf8_lossy(&original).to_string(),
+                    format!("a/{path} (original)"),
+                )
+            };
+            let current = fs::read_to_string(&file)?;
+            let file_changes =
+                Changes::between(&original, &current, &original_name, &format!("b/{path}"));
+            changes.unified += &file_changes.unified;
+            changes.insertions += file_changes.insertions;
+            changes.deletions += file_changes.deletions;
+        }
+        Ok(changes)
+    }
+
+    // Compare two texts, which the diff calls by the given names
+    pub fn between(old: &str, new: &str, old_name: &str, new_name: &str) -> Changes {
+        let diff = TextDiff::from_lines(old, new);
+
+        let mut insertions = 0;
+        let mut deletions = 0;
+        for change in diff.iter_all_changes() {
+            match change.tag() {
+                ChangeTag::Insert => insertions += 1,
+                ChangeTag::Delete => deletions += 1,
+                ChangeTag::Equal => {}
+            }
+        }
+        let unified = diff
+            .unified_diff()
+            .context_radius(3)
+            .header(old_name, new_name)
+            .to_string();
+        Changes {
+            unified,
+            insertions,
+            deletions,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.insertions + self.deletions == 0
+    }
+
+    // The unified diff, with insertions in green and deletions in red
+    pub fn colored(&self) -> String {
+        colored(&self.unified)
+    }
+
+    fn stat(&self) -> String {
+        format!(
+            "{} {}",
+            style(format!("+{}", self.insertions)).green(),
+            style(format!("-{}", self.deletions)).red()
+        )
+    }
+}
+
+// A diff, with insertions in green and deletions in red
+pub fn colored(diff: &str) -> String {
+    let mut colored = String::new();
+    for line in diff.lines() {
+        let line = if line.starts_with("+++") || line.starts_with("---") {
+            style(line).bold()
+        } else if line.starts_with('+') {
+            style(line).green()
+        } else if line.starts_with('-') {
+            style(line).red()
+        } else if line.starts_with("@@") {
+            style(line).cyan()
+        } else {
+            style(line)
+        };
+        let _ = writeln!(colored, "{line}");
+    }
+    colored
+}
+
+// Print how the learner changed the given exercises
+pub fn diff(exercises: &[&Exercise]) -> Result<(), ()> {
+    for exercise in exercises {
+        let changes = changes(exercise)?;
+        if !changes.is_empty() {
+            print!("{}", changes.colored());
+        }
+    }
+    Ok(())
+}
+
+// Print how many lines the learner changed in the given exercises,
+// grouped by topic
+pub fn stat(exercises: &[&Exercise]) -> Result<(), ()> {
+    let mut topics: Vec<(&str, Vec<(&Exercise, Changes)>)> = Vec::new();
+    for &exercise in exercises {
+        let topic = exercise
+            .path
+            .parent()
+            .and_then(Path::file_name)
+            .and_then(|topic| topic.to_str())
+            .unwrap_or("exercises");
+        let changes = changes(exercise)?;
+        match topics.iter_mut().find(|(name, _)| *name == topic) {
+            Some((_, exercises)) => exercises.push((exercise, changes)),
+            None => topics.push((topic, vec![(exercise, changes)])),
+        }
+    }
+
+    for (topic, exercises) in topics {
+        let changed: Vec<_> = exercises.iter().filter(|(_, c)| !c.is_empty()).collect();
+        let total = Changes {
+            unified: String::new(),
+            insertions: changed.iter().map(|(_, c)| c.insertions).sum(),
+            deletions: changed.iter().map(|(_, c)| c.deletions).sum(),
+        };
+        println!(
+            "{}: {} of {} exercise(s) changed, {}",
+            style(topic).bold(),
+            changed.len(),
+            exercises.len(),
+            total.stat()
+        );
+        for (exercise, changes) in changed {
+            println!("  {:<24} {}", exercise.name, changes.stat());
+        }
+    }
+    Ok(())
+}
+
+// How the exercise was changed, which is unknown if it has no original
+// version, like when it was added to the course later
+fn changes(exercise: &Exercise) -> Result<Changes, ()> {
+    if !pristine::has_original(&exercise.root()) {
+        warn!("{} has no original version to compare with", exercise);
+        return Ok(Changes {
+            unified: String::new(),
+            insertions: 0,
+            deletions: 0,
+        });
+    }
+    Changes::of(exercise).map_err(|e| {
+        warn!(
+            "Failed to compare {} with its original version: {}",
+            exercise, e
+        );
+    })
+}
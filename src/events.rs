@@ -0,0 +1,239 @@
+use crate::bench::Benchmark;
+use crate::diagnostics::Diagnostic;
+use crate::exercise::{Exercise, Grade, Mode, Outcome};
+use serde::Serialize;
+use std::io::{self, Write};
+use std::path::Path;
+
+// An event emitted with `--format json`.
+// Every event is written to stdout as a single line of JSON.
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+    // Grading of an exercise started. When several exercises are graded
+    // at the same time, the events of the others may come before its result.
+    Started {
+        exercise: &'a str,
+        path: &'a Path,
+        mode: Mode,
+    },
+    // The exercise was compiled, successfully or not
+    Compiled {
+        exercise: &'a str,
+        success: bool,
+        duration_ms: u128,
+        diagnostics: &'a [Diagnostic],
+        stdout: &'a str,
+        stderr: &'a str,
+    },
+    // The compiled exercise was run, successfully or not
+    Ran {
+        exercise: &'a str,
+        success: bool,
+        duration_ms: u128,
+        stdout: &'a str,
+        stderr: &'a str,
+        // The resource limit the exercise was stopped for, if any
+        limit: Option<String>,
+        // The seed the property tests ran with, if the exercise has any
+        #[serde(skip_serializing_if = "Option::is_none")]
+        seed: Option<u64>,
+        // The output the exercise should have printed instead, if it
+        // printed something else
+        #[serde(skip_serializing_if = "Option::is_none")]
+        expected_stdout: Option<&'a str>,
+    },
+    // The running time of the exercise was measured after its tests passed
+    Benchmarked {
+        exercise: &'a str,
+        #[serde(flatten)]
+        benchmark: &'a Benchmark,
+    },
+    // The exercise isn't formatted the way rustfmt formats it
+    Unformatted {
+        exercise: &'a str,
+        diff: &'a str,
+    },
+    // The exercise compiled and ran successfully
+    Passed {
+        exercise: &'a str,
+        mode: Mode,
+        duration_ms: u128,
+        // Whether the `I AM NOT DONE` comment was removed
+        done: bool,
+    },
+    // The exercise wasn't graded, since it is done and unchanged since it
+    // was verified to pass, or for the given reason
+    Skipped {
+        exercise: &'a str,
+        mode: Mode,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reason: Option<&'a str>,
+    },
+    // Regions the learner must not change, like the test module, differ
+    // from the original exercise, so it wasn't compiled
+    TestsModified {
+        exercise: &'a str,
+        regions: &'a [String],
+    },
+    // The exercise failed to compile or run
+    Failed {
+        exercise: &'a str,
+        mode: Mode,
+        duration_ms: u128,
+        reason: &'a str,
+    },
+    // An exercise as shown by `rustlings list`
+    Exercise {
+        name: &'a str,
+        path: &'a Path,
+        mode: Mode,
+        done: bool,
+    },
+    // How many exercises are done
+    Progress {
+        done: usize,
+        total: usize,
+    },
+    // The hint of an exercise
+    Hint {
+        exercise: &'a str,
+        hint: &'a str,
+    },
+    // Something went wrong before any exercise could be graded
+    Error {
+        message: &'a str,
+    },
+}
+
+pub fn emit(event: &Event) {
+    let line = serde_json::to_string(event).expect("Failed to serialize event");
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    // Just like `rustlings list`, exit quietly when the output is piped
+    // into a program that stopped reading
+    writeln!(handle, "{line}").unwrap_or_else(|e| match e.kind() {
+        io::ErrorKind::BrokenPipe => std::process::exit(0),
+        _ => std::process::exit(1),
+    });
+}
+
+// Emit that grading the given exercise begins
+pub fn emit_started(exercise: &Exercise) {
+    emit(&Event::Started {
+        exercise: &exercise.name,
+        path: &exercise.path,
+        mode: exercise.mode,
+    });
+}
+
+// Emit the events for the grade of the given exercise, which follow the
+// `started` event.
+// Returns whether the exercise passed.
+pub fn emit_grade(exercise: &Exercise, grade: &Grade) -> bool {
+    let name = exercise.name.as_str();
+    if let Outcome::TestsModified(regions) = &grade.outcome {
+        emit(&Event::TestsModified {
+            exercise: name,
+            regions,
+        });
+        emit(&Event::Failed {
+            exercise: name,
+            mode: exercise.mode,
+            duration_ms: 0,
+            reason: "tests_modified",
+        });
+        return false;
+    }
+    if let Outcome::Skipped(reason) = &grade.outcome {
+        emit(&Event::Skipped {
+            exercise: name,
+            mode: exercise.mode,
+            reason: Some(reason),
+        });
+        return false;
+    }
+
+    let no_diagnostics: &[Diagnostic] = &[];
+    let (compile_output, run_output) = match &grade.outcome {
+        // A compile_fail exercise passes by failing to compile
+        Outcome::Passed(output) if matches!(exercise.mode, Mode::CompileFail) => {
+            (Some(output), None)
+        }
+        Outcome::CompileFailed(output) | Outcome::WrongErrors(output, _) => (Some(output), None),
+        Outcome::TestsModified(_) | Outcome::Skipped(_) | Outcome::UnexpectedlyCompiled => {
+            (None, None)
+        }
+        Outcome::Passed(output)
+        | Outcome::RunFailed(output)
+        | Outcome::LimitExceeded(_, output)
+        | Outcome::TooSlow(_, output)
+        | Outcome::WrongOutput(output, _)
+        | Outcome::Unformatted(output, _) => (None, Some(output)),
+    };
+    emit(&Event::Compiled {
+        exercise: name,
+        success: compile_output.is_none(),
+        duration_ms: grade.compile_time.as_millis(),
+        diagnostics: compile_output.map_or(no_diagnostics, |o| &o.diagnostics),
+        stdout: compile_output.map_or("", |o| &o.stdout),
+        stderr: compile_output.map_or("", |o| &o.stderr),
+    });
+    if let Some(output) = run_output {
+        emit(&Event::Ran {
+            exercise: name,
+            success: grade.outcome.passed(),
+            duration_ms: grade.run_time.unwrap_or_default().as_millis(),
+            stdout: &output.stdout,
+            stderr: &output.stderr,
+            limit: output.limit.map(|limit| limit.to_string()),
+            seed: grade.seed,
+            expected_stdout: match &grade.outcome {
+                Outcome::WrongOutput(_, expected) => Some(expected),
+                _ => None,
+            },
+        });
+    }
+    if let Outcome::Unformatted(_, diff) = &grade.outcome {
+        emit(&Event::Unformatted {
+            exercise: name,
+            diff,
+        });
+    }
+    if let Some(benchmark) = &grade.bench {
+        emit(&Event::Benchmarked {
+            exercise: name,
+            benchmark,
+        });
+    }
+
+    let duration_ms = grade.duration().as_millis();
+    let reason = match &grade.outcome {
+        Outcome::Passed(_) => {
+            emit(&Event::Passed {
+                exercise: name,
+                mode: exercise.mode,
+                duration_ms,
+                done: exercise.looks_done(),
+            });
+            return true;
+        }
+        Outcome::CompileFailed(_) => "compile",
+        Outcome::RunFailed(_) => "run",
+        Outcome::LimitExceeded(..) => "limit",
+        Outcome::TestsModified(_) => "tests_modified",
+        Outcome::TooSlow(..) => "too_slow",
+        Outcome::Skipped(_) => "skipped",
+        Outcome::WrongOutput(..) => "wrong_output",
+        Outcome::UnexpectedlyCompiled => "compiled",
+        Outcome::WrongErrors(..) => "wrong_errors",
+        Outcome::Unformatted(..) => "unformatted",
+    };
+    emit(&Event::Failed {
+        exercise: name,
+        mode: exercise.mode,
+        duration_ms,
+        reason,
+    });
+    false
+}
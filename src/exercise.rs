@@ -1,35 +1,49 @@
+use crate::allocations;
+use crate::bench::{self, Bench, Benchmark, BENCH_LOCK, BENCH_MODULE, BENCH_TEST};
+use crate::cargo::{self, offline_args, CargoCommand, CargoProject};
+use crate::diagnostics::{self, Diagnostic, CARGO_JSON_ARGS, RUSTC_JSON_ARGS};
+use crate::limits::{run_compiled, run_limited, Limit, DEFAULT_TIMEOUT};
+use crate::property;
+use crate::tamper;
 use regex::Regex;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::env;
 use std::fmt::{self, Display, Formatter};
-use std::fs::{self, remove_file, File};
-use std::io::Read;
-use std::path::PathBuf;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
 use std::process::{self, Command};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{OnceLock, RwLockReadGuard};
+use std::time::{Duration, Instant};
 
 const RUSTC_COLOR_ARGS: &[&str] = &["--color", "always"];
-const RUSTC_EDITION_ARGS: &[&str] = &["--edition", "2021"];
+// The edition exercises are compiled with unless info.toml says otherwise
+pub const DEFAULT_EDITION: &str = "2021";
 const I_AM_DONE_REGEX: &str = r"(?m)^\s*///?\s*I\s+AM\s+NOT\s+DONE";
 const CONTEXT: usize = 2;
-const CLIPPY_CARGO_TOML_PATH: &str = "./exercises/clippy/Cargo.toml";
-const BUILD_SCRIPT_CARGO_TOML_PATH: &str = "./exercises/tests/Cargo.toml";
+// The module hidden tests are compiled into, next to the exercise's own tests
+const HIDDEN_TESTS_MODULE: &str = "hidden_tests";
+// The directory of a proc_macro exercise holding its proc-macro crate, which
+// the rest of the exercise uses by the same name
+pub const PROC_MACRO_CRATE: &str = "macros";
 
-// Get a temporary file name that is hopefully unique
-#[inline]
-fn temp_file() -> String {
-    let thread_id: String = format!("{:?}", std::thread::current().id())
-        .chars()
-        .filter(|c| c.is_alphanumeric())
-        .collect();
-
-    format!("./temp_{}_{thread_id}", process::id())
+// Get a scratch directory that is unique to this compilation.
+// The process id keeps concurrent rustlings processes apart and the
+// counter keeps concurrent compilations within one process apart.
+fn scratch_dir() -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let id = COUNTER.fetch_add(1, Ordering::SeqCst);
+    env::temp_dir().join(format!("rustlings_{}_{id}", process::id()))
 }
 
 // The mode of the exercise.
-#[derive(Deserialize, Copy, Clone, Debug)]
+#[derive(Deserialize, Serialize, Copy, Clone, Debug, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum Mode {
     // Indicates that the exercise should be compiled as a binary
+    #[default]
     Compile,
     // Indicates that the exercise should be compiled as a test harness
     Test,
@@ -37,6 +51,29 @@ pub enum Mode {
     Clippy,
     // Indicates that the exercise should be run using cargo with build script
     BuildScript,
+    // Indicates that the exercise's tests should be run with Miri, which
+    // catches undefined behavior
+    Miri,
+    // Indicates that the exercise must fail to compile with the error codes
+    // in `expect_error`
+    #[serde(rename = "compile_fail")]
+    CompileFail,
+    // Indicates that the exercise should be compiled as a library and its
+    // doc-tests run with rustdoc
+    Doctest,
+    // Indicates that the exercise should compile and be formatted the way
+    // rustfmt formats it
+    Fmt,
+    // Indicates that the exercise should be built and graded with cargo, as
+    // configured in its `cargo` table
+    Cargo,
+    // Indicates that the exercise is a proc-macro crate along with a crate
+    // using it, which are built as a workspace and tested with cargo
+    #[serde(rename = "proc_macro")]
+    ProcMacro,
+    // Indicates that the exercise should be checked by running its
+    // `command`, whose result counts like that of a test run
+    Custom,
 }
 
 #[derive(Deserialize)]
@@ -46,16 +83,82 @@ pub struct ExerciseList {
 
 // A representation of a rustlings exercise.
 // This is deserialized from the accompanying info.toml file
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Default)]
 pub struct Exercise {
     // Name of the exercise
     pub name: String,
-    // The path to the file containing the exercise's source code
+    // The path to the file containing the exercise's source code, or to a
+    // directory holding its crate root along with its submodules
     pub path: PathBuf,
     // The mode of the exercise (Test, Compile, or Clippy)
     pub mode: Mode,
     // The hint text associated with the exercise
     pub hint: String,
+    // The number of seconds the compiled exercise may run for
+    #[serde(default)]
+    pub timeout: Option<u64>,
+    // A file with tests the learner doesn't see, which `rustlings cicvverify`
+    // compiles along with the exercise. Only used for test exercises.
+    #[serde(default)]
+    pub hidden_tests: Option<PathBuf>,
+    // A file with property tests, which check the exercise against a
+    // reference implementation with random inputs. Only used for test
+    // exercises.
+    #[serde(default)]
+    pub properties: Option<PathBuf>,
+    // How to measure the running time of the exercise once its tests pass,
+    // and how fast it may grow. Only used for test exercises.
+    #[serde(default)]
+    pub bench: Option<Bench>,
+    // Whether every test must free the memory it allocates, and nothing
+    // may be freed twice. Only used for test exercises.
+    #[serde(default)]
+    pub track_allocations: bool,
+    // What a compiled exercise must print, given inline or as the path of a
+    // `.expected` file. Differences in trailing whitespace and surrounding
+    // blank lines don't count.
+    #[serde(default)]
+    pub expected_output: Option<String>,
+    #[serde(default)]
+    pub expected_output_file: Option<PathBuf>,
+    // The input the compiled exercise reads from stdin
+    #[serde(default)]
+    pub stdin: Option<String>,
+    // The error codes rustc must reject a compile_fail exercise with, like
+    // `E0382`. Errors with other codes fail the exercise.
+    #[serde(default)]
+    pub expect_error: Vec<String>,
+    // Whether the exercise must also be formatted the way rustfmt formats
+    // it, once it passes. Always the case for fmt exercises.
+    #[serde(default)]
+    pub check_fmt: bool,
+    // The manifest, build script, features and cargo subcommand of a cargo
+    // exercise
+    #[serde(default)]
+    pub cargo: Option<CargoProject>,
+    // The crates the exercise uses, declared like in a Cargo.toml. They
+    // are resolved from the course's vendored crates without network
+    // access, so compile and test exercises using them are built by cargo.
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, toml::Value>,
+    // The program checking a custom exercise followed by its arguments.
+    // `{path}`, `{name}`, `{edition}` and `{tmpdir}` in them are replaced
+    // with the path of the exercise, its name, its edition and a scratch
+    // directory of its own.
+    #[serde(default)]
+    pub command: Vec<String>,
+    // The edition the exercise is compiled with, if not the default one
+    #[serde(default)]
+    pub edition: Option<String>,
+    // Extra flags for rustc, like `-D warnings` or `-C opt-level=1`, which
+    // doctests pass to rustdoc and cargo based modes through
+    // CARGO_ENCODED_RUSTFLAGS
+    #[serde(default)]
+    pub rustc_flags: Vec<String>,
+    // The options the exercise is compiled with as `--cfg` arguments, like
+    // `loom` or `feature="serde"`
+    #[serde(default)]
+    pub cfg: Vec<String>,
 }
 
 // An enum to track of the state of an Exercise.
@@ -82,171 +185,908 @@ pub struct ContextLine {
 // The result of compiling an exercise
 pub struct CompiledExercise<'a> {
     exercise: &'a Exercise,
-    _handle: FileHandle,
+    scratch: ScratchDir,
+    // The seed the property tests run with, if there are any
+    seed: Option<u64>,
 }
 
 impl<'a> CompiledExercise<'a> {
     // Run the compiled exercise
     pub fn run(&self) -> Result<ExerciseOutput, ExerciseOutput> {
-        self.exercise.run()
+        self.exercise.run(&self.scratch, self.seed)
     }
 }
 
 // A representation of an already executed binary
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct ExerciseOutput {
     // The textual contents of the standard output of the binary
     pub stdout: String,
     // The textual contents of the standard error of the binary
     pub stderr: String,
+    // The resource limit the binary was stopped for, if any
+    pub limit: Option<Limit>,
+    // The diagnostics of a failed compilation
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ExerciseOutput {
+    // The output of a command that couldn't be run, explaining why
+    fn error(stderr: String) -> ExerciseOutput {
+        ExerciseOutput {
+            stderr,
+            ..Default::default()
+        }
+    }
+}
+
+// The outcome of grading an exercise without any user interaction
+#[derive(Debug)]
+pub enum Outcome {
+    // The exercise compiled and ran successfully
+    Passed(ExerciseOutput),
+    // The exercise could not be compiled
+    CompileFailed(ExerciseOutput),
+    // The exercise compiled, but running it failed
+    RunFailed(ExerciseOutput),
+    // The exercise compiled, but was stopped for exceeding a resource limit
+    LimitExceeded(Limit, ExerciseOutput),
+    // The named regions the learner must not change, like the test module,
+    // differ from the original exercise. Such an exercise isn't compiled.
+    TestsModified(Vec<String>),
+    // The tests passed, but the running time grows faster than the bound
+    // of the exercise's benchmark. The output is that of the tests.
+    TooSlow(Benchmark, ExerciseOutput),
+    // The exercise couldn't be graded for the given reason, like a missing
+    // tool
+    Skipped(String),
+    // The exercise ran successfully, but didn't print the expected output.
+    // Holds the output and the normalized expected stdout.
+    WrongOutput(ExerciseOutput, String),
+    // A compile_fail exercise compiled, although it must not
+    UnexpectedlyCompiled,
+    // A compile_fail exercise failed to compile, but not with the expected
+    // error codes. Holds the compiler output and the codes it reported.
+    WrongErrors(ExerciseOutput, Vec<String>),
+    // The exercise passed, but isn't formatted the way rustfmt formats it.
+    // Holds the output and the diff rustfmt printed.
+    Unformatted(ExerciseOutput, String),
+}
+
+impl Outcome {
+    pub fn passed(&self) -> bool {
+        matches!(self, Outcome::Passed(_))
+    }
+}
+
+// The outcome of grading an exercise along with how long each step took
+#[derive(Debug)]
+pub struct Grade {
+    pub outcome: Outcome,
+    pub compile_time: Duration,
+    // How long the compiled exercise ran, if it got that far
+    pub run_time: Option<Duration>,
+    // How many tests passed, for test exercises that got to run
+    pub tests: Option<TestSummary>,
+    // The seed the property tests ran with, if there are any
+    pub seed: Option<u64>,
+    // The measured running times, for exercises with a benchmark that
+    // passed their tests
+    pub bench: Option<Benchmark>,
+}
+
+// How to grade an exercise, in addition to compiling and running it
+#[derive(Clone, Debug, Default)]
+pub struct GradeOptions {
+    // The directory the `hidden_tests` paths in info.toml are relative to.
+    // Hidden tests are only added when this is set.
+    pub hidden_tests_dir: Option<PathBuf>,
+    // The seed for the property tests, a random one by default
+    pub seed: Option<u64>,
+}
+
+// How many tests passed and failed
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct TestCounts {
+    pub passed: usize,
+    pub failed: usize,
+}
+
+// The results of the tests of an exercise, with the hidden tests
+// counted separately from those the learner can see
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct TestSummary {
+    pub visible: TestCounts,
+    pub hidden: TestCounts,
+}
+
+impl TestSummary {
+    // Count the test results in the output of a test harness
+    fn parse(output: &str) -> TestSummary {
+        let mut summary = TestSummary::default();
+        for line in output.lines() {
+            let Some((name, result)) = line
+                .strip_prefix("test ")
+                .and_then(|line| line.split_once(" ... "))
+            else {
+                continue;
+            };
+            let counts = if name.starts_with(&format!("{HIDDEN_TESTS_MODULE}::")) {
+                &mut summary.hidden
+            } else {
+                &mut summary.visible
+            };
+            match result.trim() {
+                "ok" => counts.passed += 1,
+                "FAILED" => counts.failed += 1,
+                _ => {}
+            }
+        }
+        summary
+    }
+}
+
+impl Grade {
+    // The grade of an exercise that didn't get to run
+    fn not_run(outcome: Outcome, compile_time: Duration, seed: Option<u64>) -> Grade {
+        Grade {
+            outcome,
+            compile_time,
+            run_time: None,
+            tests: None,
+            seed,
+            bench: None,
+        }
+    }
+
+    // How long grading took in total
+    pub fn duration(&self) -> Duration {
+        self.compile_time + self.run_time.unwrap_or_default()
+    }
+}
+
+// A directory holding everything a single compilation produces:
+// the binary, a generated Cargo.toml and cargo's target directory.
+// It is removed again once the compiled exercise is dropped.
+struct ScratchDir {
+    path: PathBuf,
 }
 
-struct FileHandle;
+impl ScratchDir {
+    fn new() -> ScratchDir {
+        let path = scratch_dir();
+        // A directory left behind by an earlier process with the same id
+        let _ignored = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).expect("Failed to create a scratch directory!");
+        ScratchDir { path }
+    }
+
+    // The path of the binary produced by rustc
+    fn binary(&self) -> PathBuf {
+        self.path.join(format!("exercise{}", env::consts::EXE_SUFFIX))
+    }
+
+    // The path of the library produced by rustc for the given crate
+    fn library(&self, crate_name: &str) -> PathBuf {
+        self.path.join(format!("lib{crate_name}.rlib"))
+    }
 
-impl Drop for FileHandle {
+    // The path of the Cargo.toml generated for cargo based modes
+    fn manifest(&self) -> PathBuf {
+        self.path.join("Cargo.toml")
+    }
+}
+
+impl Drop for ScratchDir {
     fn drop(&mut self) {
-        clean();
+        let _ignored = fs::remove_dir_all(&self.path);
     }
 }
 
 impl Exercise {
-    pub fn compile(&self) -> Result<CompiledExercise, ExerciseOutput> {
+    // Compile the exercise as it is, which grading does with its hidden and
+    // property tests added
+    #[cfg(test)]
+    pub fn compile(&self) -> Result<CompiledExercise<'_>, ExerciseOutput> {
+        self.compile_with_modules(&[], None, false)
+    }
+
+    // Compile the exercise with the given modules added to it, if it is a
+    // test exercise. The compiled exercise runs its property tests with the
+    // given seed. Test exercises can be compiled with optimizations, to
+    // measure them, which leaves out allocation tracking.
+    fn compile_with_modules(
+        &self,
+        modules: &[String],
+        seed: Option<u64>,
+        optimize: bool,
+    ) -> Result<CompiledExercise<'_>, ExerciseOutput> {
+        let scratch = ScratchDir::new();
+        let binary = scratch.binary();
+        let track_allocations = self.track_allocations && !optimize;
+        let source = match self.mode {
+            Mode::Test if !modules.is_empty() || track_allocations => {
+                let source = self.scratch_root(&scratch);
+                let exercise = fs::read_to_string(self.root())
+                    .expect("We were unable to read the exercise file!");
+                let mut text = format!("{exercise}\n{}", modules.concat());
+                if track_allocations {
+                    text = allocations::instrument(&text) + &allocations::module();
+                }
+                fs::write(&source, text).expect("Failed to write the exercise with its extra tests.");
+                source
+            }
+            _ => self.root(),
+        };
         let cmd = match self.mode {
-            Mode::Compile => Command::new("rustc")
-                .args(&[self.path.to_str().unwrap(), "-o", &temp_file()])
-                .args(RUSTC_COLOR_ARGS)
-                .args(RUSTC_EDITION_ARGS)
+            Mode::Compile | Mode::Test if !self.dependencies.is_empty() => {
+                self.cargo_build(&scratch, &source, optimize)
+            }
+            Mode::Compile | Mode::CompileFail | Mode::Fmt => Command::new("rustc")
+                .arg(&source)
+                .arg("-o")
+                .arg(&binary)
+                .args(RUSTC_JSON_ARGS)
+                .args(self.rustc_args())
                 .output(),
             Mode::Test => Command::new("rustc")
-                .args(&["--test", self.path.to_str().unwrap(), "-o", &temp_file()])
-                .args(RUSTC_COLOR_ARGS)
-                .args(RUSTC_EDITION_ARGS)
+                .arg("--test")
+                .args(optimize.then_some("-O"))
+                .arg(&source)
+                .arg("-o")
+                .arg(&binary)
+                .args(RUSTC_JSON_ARGS)
+                .args(self.rustc_args())
                 .output(),
-            Mode::Clippy => {
-                let cargo_toml = format!(
-                    r#"[package]
-name = "{}"
-version = "0.0.1"
-edition = "2021"
-[[bin]]
-name = "{}"
-path = "{}.rs""#,
-                    self.name, self.name, self.name
-                );
-                let cargo_toml_error_msg = if env::var("NO_EMOJI").is_ok() {
-                    "Failed to write Clippy Cargo.toml file."
-                } else {
-                    "Failed to write 📎 Clippy 📎 Cargo.toml file."
+            // The doc-tests link against the library, like they would in a
+            // cargo package
+            Mode::Doctest => Command::new("rustc")
+                .args(["--crate-type", "lib", "--crate-name"])
+                .arg(self.crate_name())
+                .arg(&source)
+                .arg("-o")
+                .arg(scratch.library(&self.crate_name()))
+                .args(RUSTC_JSON_ARGS)
+                .args(self.rustc_args())
+                .output(),
+            Mode::Clippy | Mode::BuildScript | Mode::Cargo | Mode::ProcMacro => {
+                let project = self.cargo_project();
+                let manifest = match self.manifest(&project) {
+                    Ok(manifest) => manifest,
+                    Err(e) => {
+                        let template = project.manifest.as_deref().unwrap_or(&self.path);
+                        return Err(unreadable_output("Cargo.toml template", template, e));
+                    }
                 };
-                fs::write(CLIPPY_CARGO_TOML_PATH, cargo_toml).expect(cargo_toml_error_msg);
-                // To support the ability to run the clippy exercises, build
-                // an executable, in addition to running clippy. With a
-                // compilation failure, this would silently fail. But we expect
-                // clippy to reflect the same failure while compiling later.
-                Command::new("rustc")
-                    .args(&[self.path.to_str().unwrap(), "-o", &temp_file()])
-                    .args(RUSTC_COLOR_ARGS)
-                    .args(RUSTC_EDITION_ARGS)
-                    .output()
-                    .expect("Failed to compile!");
-                // Due to an issue with Clippy, a cargo clean is required to catch all lints.
-                // See https://github.com/rust-lang/rust-clippy/issues/2604
-                // This is already fixed on Clippy's master branch. See this issue to track merging into Cargo:
-                // https://github.com/rust-lang/rust-clippy/issues/3837
-                Command::new("cargo")
-                    .args(&["clean", "--manifest-path", CLIPPY_CARGO_TOML_PATH])
-                    .args(RUSTC_COLOR_ARGS)
-                    .output()
-                    .expect("Failed to run 'cargo clean'");
-                Command::new("cargo")
-                    .args(&["clippy", "--manifest-path", CLIPPY_CARGO_TOML_PATH])
-                    .args(RUSTC_COLOR_ARGS)
-                    .args(&["--", "-D", "warnings", "-D", "clippy::float_cmp"])
-                    .output()
-            },
-            Mode::BuildScript => {
-                let cargo_toml = format!(
-                    r#"[package]
-name = "{}"
-version = "0.0.1"
-edition = "2021"
-[[bin]]
-name = "{}"
-path = "{}.rs""#,
-                    self.name, self.name, self.name
-                );
-                let cargo_toml_error_msg = if env::var("NO_EMOJI").is_ok() {
-                    "Failed to write Clippy Cargo.toml file."
-                } else {
-                    "Failed to write 📎 Clippy 📎 Cargo.toml file."
+                fs::write(scratch.manifest(), manifest)
+                    .unwrap_or_else(|e| panic!("Failed to write the Cargo.toml of {self}: {e}"));
+                if let Some(root) = self.proc_macro_root() {
+                    let dir = scratch.path.join(PROC_MACRO_CRATE);
+                    let manifest = proc_macro_toml(&root, self.edition());
+                    fs::create_dir_all(&dir)
+                        .and_then(|()| fs::write(dir.join("Cargo.toml"), manifest))
+                        .unwrap_or_else(|e| panic!("Failed to write the macros Cargo.toml of {self}: {e}"));
+                }
+                let cargo = |args: &[&str], lint_args: &[&str]| {
+                    Command::new("cargo")
+                        .args(args)
+                        .arg("--manifest-path")
+                        .arg(scratch.manifest())
+                        .args(project.feature_args())
+                        .args(offline_args())
+                        .args(RUSTC_COLOR_ARGS)
+                        .args(CARGO_JSON_ARGS)
+                        .args(lint_args)
+                        .envs(self.cargo_rustflags())
+                        .output()
                 };
-                fs::write(BUILD_SCRIPT_CARGO_TOML_PATH, cargo_toml).expect(cargo_toml_error_msg);
-
+                // The scratch directory starts without a target directory, so
+                // clippy always checks the exercise from scratch and no
+                // `cargo clean` is needed to catch all lints.
+                let output = cargo(project.command.compile_args(), project.command.lint_args());
+                match (output, project.command.build_args()) {
+                    (Ok(output), Some(build_args)) if output.status.success() => {
+                        cargo(build_args, &[])
+                    }
+                    (output, _) => output,
+                }
+            }
+            Mode::Miri => {
+                fs::write(scratch.manifest(), self.cargo_toml(&source, None))
+                    .expect("Failed to write Miri Cargo.toml file.");
+                // Only build the tests here, so that undefined behavior
+                // shows up as a failed run
                 Command::new("cargo")
-                    .args(&["test", "--manifest-path", BUILD_SCRIPT_CARGO_TOML_PATH])
+                    .args(["miri", "test", "--no-run"])
+                    .arg("--manifest-path")
+                    .arg(scratch.manifest())
+                    .args(offline_args())
+                    .args(RUSTC_COLOR_ARGS)
+                    .args(CARGO_JSON_ARGS)
+                    .envs(self.cargo_rustflags())
                     .output()
             }
+            // A custom exercise is only checked by its command, when it runs
+            Mode::Custom => {
+                return Ok(CompiledExercise {
+                    exercise: self,
+                    scratch,
+                    seed,
+                })
+            }
         }
         .expect("Failed to run 'compile' command.");
 
         if cmd.status.success() {
             Ok(CompiledExercise {
                 exercise: self,
-                _handle: FileHandle,
+                scratch,
+                seed,
             })
         } else {
-            clean();
+            // Diagnostics are on stderr for rustc and on stdout for cargo
+            let (mut diagnostics, stderr) = diagnostics::parse(&String::from_utf8_lossy(&cmd.stderr));
+            let (cargo_diagnostics, stdout) = diagnostics::parse(&String::from_utf8_lossy(&cmd.stdout));
+            diagnostics.extend(cargo_diagnostics);
             Err(ExerciseOutput {
-                stdout: String::from_utf8_lossy(&cmd.stdout).to_string(),
-                stderr: String::from_utf8_lossy(&cmd.stderr).to_string(),
+                stdout,
+                stderr,
+                limit: None,
+                diagnostics,
             })
         }
     }
 
-    fn run(&self) -> Result<ExerciseOutput, ExerciseOutput> {
-        let arg = match self.mode {
-            Mode::Test => "--show-output",
-            Mode::BuildScript => return Ok(ExerciseOutput {
-                stdout: "".to_string(),
-                stderr: "".to_string(),
-            }),
-            _ => "",
+    // Compile and run the exercise, capturing all output instead of printing it.
+    // This is safe to call for many exercises at the same time.
+    pub fn grade(&self) -> Grade {
+        self.grade_with(&GradeOptions::default())
+    }
+
+    // Like `grade`, but with the hidden tests and property tests of a test
+    // exercise added as requested.
+    // Missing hidden tests fail the exercise, so that they can't be skipped
+    // by removing them.
+    pub fn grade_with(&self, options: &GradeOptions) -> Grade {
+        let grading = BENCH_LOCK.read().unwrap_or_else(|e| e.into_inner());
+        if let Some(grade) = self.grade_modified_tests() {
+            return grade;
+        }
+        if let (Mode::Miri, false) = (self.mode, miri_available()) {
+            return Grade::not_run(
+                Outcome::Skipped(
+                    "Miri is not installed, install it with `rustup component add miri`".to_string(),
+                ),
+                Duration::ZERO,
+                None,
+            );
+        }
+        let expected = match self.expected_output() {
+            Ok(expected) => expected,
+            Err(e) => {
+                let path = self.expected_output_file.as_deref().unwrap_or(&self.path);
+                return unreadable("expected output", path, e);
+            }
+        };
+        let mut modules = Vec::new();
+        let mut seed = None;
+        if let Mode::Test = self.mode {
+            if let (Some(dir), Some(path)) = (&options.hidden_tests_dir, &self.hidden_tests) {
+                match fs::read_to_string(dir.join(path)) {
+                    Ok(hidden_tests) => modules.push(format!(
+                        "#[cfg(test)]\nmod {HIDDEN_TESTS_MODULE} {{\n{hidden_tests}\n}}\n"
+                    )),
+                    Err(e) => return unreadable("hidden tests", &dir.join(path), e),
+                }
+            }
+            if let Some(path) = &self.properties {
+                match fs::read_to_string(path) {
+                    Ok(properties) => modules.push(property::modules(&properties)),
+                    Err(e) => return unreadable("property tests", path, e),
+                }
+                seed = Some(options.seed.unwrap_or_else(property::random_seed));
+            }
+        }
+
+        let start = Instant::now();
+        let compiled = self.compile_with_modules(&modules, seed, false);
+        let compile_time = start.elapsed();
+
+        if let Mode::CompileFail = self.mode {
+            let outcome = match compiled {
+                Ok(_) => Outcome::UnexpectedlyCompiled,
+                Err(output) => self.check_errors(output),
+            };
+            return Grade::not_run(outcome, compile_time, seed);
+        }
+        let compiled = match compiled {
+            Ok(compiled) => compiled,
+            Err(output) => {
+                return Grade::not_run(Outcome::CompileFailed(output), compile_time, seed)
+            }
+        };
+        let start = Instant::now();
+        let outcome = match compiled.run() {
+            Ok(output) => check_output(output, expected.as_deref()),
+            Err(output) => failed_run(output),
+        };
+        let tests = match (&outcome, self.mode) {
+            (
+                Outcome::Passed(output) | Outcome::RunFailed(output),
+                Mode::Test | Mode::Miri | Mode::Doctest | Mode::BuildScript | Mode::ProcMacro | Mode::Custom,
+            ) => Some(TestSummary::parse(&output.stdout)),
+            _ => None,
+        };
+        let outcome = match outcome {
+            Outcome::Passed(output) => self.check_formatting(output),
+            outcome => outcome,
+        };
+        let (outcome, bench) = match (outcome, &self.bench) {
+            (Outcome::Passed(output), Some(bench)) if matches!(self.mode, Mode::Test) => {
+                self.measure(bench, output, grading)
+            }
+            (outcome, _) => (outcome, None),
+        };
+        Grade {
+            outcome,
+            compile_time,
+            run_time: Some(start.elapsed()),
+            tests,
+            seed,
+            bench,
+        }
+    }
+
+    // Measure the running time of the exercise, compiled with
+    // optimizations, for increasing input sizes, after its tests passed
+    // with the given output.
+    // Returns the outcome of the exercise along with the measurement, if
+    // the benchmark could be run.
+    // The benchmark waits for every other exercise being graded to let go of
+    // BENCH_LOCK, which grading this one holds until then.
+    fn measure(
+        &self,
+        bench: &Bench,
+        output: ExerciseOutput,
+        grading: RwLockReadGuard<()>,
+    ) -> (Outcome, Option<Benchmark>) {
+        let module = match fs::read_to_string(&bench.file) {
+            Ok(file) => bench::module(&file, bench.sizes.as_deref()),
+            Err(e) => {
+                let output = unreadable_output("benchmark", &bench.file, e);
+                return (Outcome::CompileFailed(output), None);
+            }
         };
-        println!("pa={}", temp_file());
-        let cmd = Command::new(&temp_file())
-            .arg(arg)
-            .output()
+        let compiled = match self.compile_with_modules(&[module], None, true) {
+            Ok(compiled) => compiled,
+            Err(output) => return (Outcome::CompileFailed(output), None),
+        };
+
+        drop(grading);
+        let _measuring = BENCH_LOCK.write().unwrap_or_else(|e| e.into_inner());
+        let mut cmd = Command::new(compiled.scratch.binary());
+        cmd.arg(format!("{BENCH_MODULE}::{BENCH_TEST}"))
+            .args(["--exact", "--nocapture"]);
+        let (bench_output, success) = run_compiled(&mut cmd, self.timeout(), None)
+            .expect("Failed to run the benchmark");
+        if !success {
+            return (failed_run(bench_output), None);
+        }
+        let benchmark = Benchmark::fit(bench.bound, bench::parse(&bench_output.stdout));
+        if benchmark.within_bound {
+            (Outcome::Passed(output), Some(benchmark))
+        } else {
+            (Outcome::TooSlow(benchmark.clone(), output), Some(benchmark))
+        }
+    }
+
+    // The outcome of a compile_fail exercise that failed to compile with the
+    // given output, which passes if rustc reported exactly the expected
+    // error codes
+    fn check_errors(&self, output: ExerciseOutput) -> Outcome {
+        let mut expected = self.expect_error.clone();
+        expected.sort();
+        expected.dedup();
+        let found = diagnostics::error_codes(&output.diagnostics);
+        if found == expected {
+            Outcome::Passed(output)
+        } else {
+            Outcome::WrongErrors(output, found)
+        }
+    }
+
+    // The grade of an exercise whose tests or locked regions were modified,
+    // if they were
+    fn grade_modified_tests(&self) -> Option<Grade> {
+        let modified = tamper::modified_regions(self);
+        (!modified.is_empty())
+            .then(|| Grade::not_run(Outcome::TestsModified(modified), Duration::ZERO, None))
+    }
+
+    // How the exercise is built with cargo. Clippy, build script and
+    // proc-macro exercises are cargo exercises with a fixed configuration.
+    pub fn cargo_project(&self) -> CargoProject {
+        match self.mode {
+            Mode::Clippy => CargoProject {
+                command: CargoCommand::Clippy,
+                ..Default::default()
+            },
+            Mode::ProcMacro => CargoProject {
+                command: CargoCommand::Test,
+                ..Default::default()
+            },
+            Mode::BuildScript => CargoProject {
+                command: CargoCommand::Test,
+                build_script: Some(self.root().with_file_name("build.rs"))
+                    .filter(|build_script| build_script.exists()),
+                ..Default::default()
+            },
+            _ => self.cargo.clone().unwrap_or_default(),
+        }
+    }
+
+    // The Cargo.toml of a cargo exercise, generated from its template if
+    // it has one
+    fn manifest(&self, project: &CargoProject) -> io::Result<String> {
+        let Some(template) = &project.manifest else {
+            return Ok(self.cargo_toml(&self.root(), project.build_script.as_deref()));
+        };
+        let build_script = project.build_script.as_deref().map(manifest_path);
+        Ok(fs::read_to_string(template)?
+            .replace("{name}", &self.name)
+            .replace("{root}", &manifest_path(&self.root()))
+            .replace("{build_script}", build_script.as_deref().unwrap_or(""))
+            .replace("{edition}", self.edition())
+            .replace("{dependencies}", &cargo::dependencies_toml(&self.dependencies)))
+    }
+
+    // Generate a Cargo.toml that builds the given crate root of the
+    // exercise as a binary, or as a library if it is a `lib.rs`, along with
+    // the dependencies of the exercise. The proc-macro crate of a proc_macro
+    // exercise is one of them, and a member of the workspace.
+    // Paths are absolute since the manifest lives in a scratch directory.
+    fn cargo_toml(&self, root: &Path, build_script: Option<&Path>) -> String {
+        let mut dependencies = self.dependencies.clone();
+        let mut cargo_toml = String::new();
+        if self.proc_macro_root().is_some() {
+            cargo_toml += &format!("[workspace]\nmembers = [\"{PROC_MACRO_CRATE}\"]\n\n");
+            let mut macros = toml::value::Table::new();
+            macros.insert("path".to_string(), PROC_MACRO_CRATE.into());
+            dependencies.insert(PROC_MACRO_CRATE.to_string(), toml::Value::Table(macros));
+        }
+        cargo_toml += &format!(
+            r#"[package]
+name = "{}"
+version = "0.0.1"
+edition = "{}"
+"#,
+            self.name,
+            self.edition()
+        );
+        if let Some(build_script) = build_script {
+            cargo_toml += &format!("build = \"{}\"\n", manifest_path(build_script));
+        }
+        let target = if root.ends_with("lib.rs") { "[lib]" } else { "[[bin]]" };
+        cargo_toml += &format!(
+            r#"{target}
+name = "{}"
+path = "{}""#,
+            self.name,
+            manifest_path(root)
+        );
+        if !dependencies.is_empty() {
+            cargo_toml += "\n\n";
+            cargo_toml += &cargo::dependencies_toml(&dependencies);
+        }
+        cargo_toml
+    }
+
+    // Build a compile or test exercise that uses crates with cargo, from
+    // the given crate root, and put the executable where rustc would have.
+    // Test exercises are built as a test harness.
+    fn cargo_build(&self, scratch: &ScratchDir, root: &Path, optimize: bool) -> io::Result<process::Output> {
+        fs::write(scratch.manifest(), self.cargo_toml(root, None))?;
+        let build: &[&str] = match self.mode {
+            Mode::Test => &["test", "--no-run"],
+            _ => &["build"],
+        };
+        let output = Command::new("cargo")
+            .args(build)
+            .args(optimize.then_some("--release"))
+            .arg("--manifest-path")
+            .arg(scratch.manifest())
+            .args(offline_args())
+            .args(RUSTC_COLOR_ARGS)
+            .args(CARGO_JSON_ARGS)
+            .envs(self.cargo_rustflags())
+            .output()?;
+        if output.status.success() {
+            let executable = cargo::executable(&String::from_utf8_lossy(&output.stdout))
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "cargo built no executable"))?;
+            fs::copy(executable, scratch.binary())?;
+        }
+        Ok(output)
+    }
+
+    fn run(&self, scratch: &ScratchDir, seed: Option<u64>) -> Result<ExerciseOutput, ExerciseOutput> {
+        let args: &[&str] = match self.mode {
+            // Captured output is freed after the test, so it would look
+            // like a leak
+            Mode::Test if self.track_allocations => &["--nocapture", "--test-threads=1"],
+            Mode::Test => &["--show-output"],
+            Mode::Fmt => {
+                return Ok(ExerciseOutput::default())
+            }
+            Mode::Miri => return self.run_miri(scratch),
+            Mode::Doctest => return self.run_doctests(scratch),
+            Mode::Custom => return self.run_custom(scratch),
+            Mode::Clippy | Mode::BuildScript | Mode::Cargo | Mode::ProcMacro => {
+                return self.run_cargo(scratch)
+            }
+            _ => &[],
+        };
+        let mut cmd = Command::new(scratch.binary());
+        cmd.args(args)
+            .envs(seed.map(|seed| (property::SEED_ENV, seed.to_string())));
+        let result = run_compiled(&mut cmd, self.timeout(), self.stdin.as_deref())
             .expect("Failed to run 'run' command");
+        exited(result)
+    }
+
+    // Run the package built by cargo, the way its cargo subcommand does
+    fn run_cargo(&self, scratch: &ScratchDir) -> Result<ExerciseOutput, ExerciseOutput> {
+        let project = self.cargo_project();
+        let Some(args) = project.command.run_args() else {
+            return Ok(ExerciseOutput::default());
+        };
+        let mut cmd = Command::new("cargo");
+        cmd.args(args)
+            .arg("--manifest-path")
+            .arg(scratch.manifest())
+            .args(project.feature_args())
+            .args(offline_args())
+            .args(project.command.program_args())
+            .envs(self.cargo_rustflags());
+        let result = run_limited(&mut cmd, self.timeout(), self.stdin.as_deref())
+            .expect("Failed to run cargo");
+        exited(result)
+    }
 
-        let output = ExerciseOutput {
-            stdout: String::from_utf8_lossy(&cmd.stdout).to_string(),
-            stderr: String::from_utf8_lossy(&cmd.stderr).to_string(),
+    // Run the command of a custom exercise, with its placeholders filled in.
+    // A command that can't be started fails the exercise.
+    fn run_custom(&self, scratch: &ScratchDir) -> Result<ExerciseOutput, ExerciseOutput> {
+        let fill = |arg: &String| {
+            arg.replace("{path}", &self.path.display().to_string())
+                .replace("{name}", &self.name)
+                .replace("{tmpdir}", &scratch.path.display().to_string())
+                .replace("{edition}", self.edition())
         };
+        let Some((program, args)) = self.command.split_first() else {
+            return Err(ExerciseOutput::error(format!(
+                "No command to check {self} with in info.toml\n"
+            )));
+        };
+        let mut cmd = Command::new(fill(program));
+        cmd.args(args.iter().map(fill)).envs(self.cargo_rustflags());
+        match run_limited(&mut cmd, self.timeout(), self.stdin.as_deref()) {
+            Ok(result) => exited(result),
+            Err(e) => Err(ExerciseOutput::error(format!(
+                "Failed to run `{}`: {e}\n",
+                fill(program)
+            ))),
+        }
+    }
 
+    // Run the tests built by `cargo miri test --no-run` with Miri, within
+    // the limits of the exercise.
+    // Miri reports undefined behavior on stderr.
+    fn run_miri(&self, scratch: &ScratchDir) -> Result<ExerciseOutput, ExerciseOutput> {
+        let mut cmd = Command::new("cargo");
+        cmd.args(["miri", "test", "--quiet"])
+            .arg("--manifest-path")
+            .arg(scratch.manifest())
+            .args(offline_args())
+            .args(RUSTC_COLOR_ARGS)
+            .args(["--", "--show-output"])
+            .envs(self.cargo_rustflags());
+        let result = run_limited(&mut cmd, self.timeout(), None)
+            .expect("Failed to run 'cargo miri test'");
+        exited(result)
+    }
+
+    // The outcome of an exercise that passed with the given output, which
+    // still fails if it must be formatted like rustfmt would and isn't
+    pub fn check_formatting(&self, output: ExerciseOutput) -> Outcome {
+        if !self.check_fmt && !matches!(self.mode, Mode::Fmt) {
+            return Outcome::Passed(output);
+        }
+        if !rustfmt_available() {
+            return Outcome::Skipped(
+                "rustfmt is not installed, install it with `rustup component add rustfmt`".to_string(),
+            );
+        }
+        let cmd = Command::new("rustfmt")
+            .args(["--check", "--color", "never", "--edition", self.edition()])
+            .arg(self.root())
+            .output()
+            .expect("Failed to run 'rustfmt --check'");
         if cmd.status.success() {
-            Ok(output)
+            return Outcome::Passed(output);
+        }
+        let diff = format!(
+            "{}{}",
+            String::from_utf8_lossy(&cmd.stdout),
+            String::from_utf8_lossy(&cmd.stderr)
+        );
+        Outcome::Unformatted(output, diff)
+    }
+
+    // Run the doc-tests of the exercise with rustdoc, against the library
+    // built from it. Each doc-test is reported like a test.
+    fn run_doctests(&self, scratch: &ScratchDir) -> Result<ExerciseOutput, ExerciseOutput> {
+        let crate_name = self.crate_name();
+        let mut cmd = Command::new("rustdoc");
+        cmd.arg("--test")
+            .arg(self.root())
+            .args(["--crate-name", &crate_name])
+            .args(self.rustc_args())
+            .arg("-L")
+            .arg(&scratch.path)
+            .arg("--extern")
+            .arg(format!("{crate_name}={}", scratch.library(&crate_name).display()));
+        let result = run_limited(&mut cmd, self.timeout(), None)
+            .expect("Failed to run 'rustdoc --test'");
+        exited(result)
+    }
+
+    // The name of the crate the exercise is compiled into, which doc-tests
+    // use it by. It is the name of the exercise file or directory.
+    fn crate_name(&self) -> String {
+        self.path
+            .file_stem()
+            .map_or("exercise".into(), |stem| stem.to_string_lossy().replace('-', "_"))
+    }
+
+    // What the compiled exercise must print, normalized, if info.toml says
+    pub fn expected_output(&self) -> io::Result<Option<String>> {
+        let expected = match (&self.expected_output, &self.expected_output_file) {
+            (Some(expected), _) => expected.clone(),
+            (None, Some(path)) => fs::read_to_string(path)?,
+            (None, None) => return Ok(None),
+        };
+        Ok(Some(normalize_output(&expected)))
+    }
+
+    // The edition the exercise is compiled with
+    pub fn edition(&self) -> &str {
+        self.edition.as_deref().unwrap_or(DEFAULT_EDITION)
+    }
+
+    // The arguments to rustc or rustdoc enabling the cfg options of the
+    // exercise
+    fn cfg_args(&self) -> Vec<String> {
+        self.cfg
+            .iter()
+            .flat_map(|cfg| ["--cfg".to_string(), cfg.clone()])
+            .collect()
+    }
+
+    // The arguments to rustc setting the edition, cfg options and extra
+    // flags of the exercise
+    fn rustc_args(&self) -> Vec<String> {
+        let mut args = vec!["--edition".to_string(), self.edition().to_string()];
+        args.extend(self.cfg_args());
+        args.extend(self.rustc_flags.iter().cloned());
+        args
+    }
+
+    // The environment variable passing the cfg options and extra flags of
+    // the exercise on to rustc when cargo builds it, if there are any.
+    // Compiling and running with cargo must set the same flags, or cargo
+    // builds the exercise again.
+    fn cargo_rustflags(&self) -> Option<(&'static str, String)> {
+        let mut flags = self.cfg_args();
+        flags.extend(self.rustc_flags.iter().cloned());
+        // Flags are separated by 0x1f, so that they may contain spaces
+        (!flags.is_empty()).then(|| ("CARGO_ENCODED_RUSTFLAGS", flags.join("\x1f")))
+    }
+
+    // How long the compiled exercise may run for
+    pub fn timeout(&self) -> Duration {
+        self.timeout.map_or(DEFAULT_TIMEOUT, Duration::from_secs)
+    }
+
+    // The crate root of the exercise. A directory exercise has a `main.rs`,
+    // or a `lib.rs` if it is a library.
+    pub fn root(&self) -> PathBuf {
+        if !self.path.is_dir() {
+            return self.path.clone();
+        }
+        let lib = self.path.join("lib.rs");
+        if lib.exists() && !self.path.join("main.rs").exists() {
+            lib
         } else {
-            Err(output)
+            self.path.join("main.rs")
+        }
+    }
+
+    // The crate root of the proc-macro crate of a proc_macro exercise
+    pub fn proc_macro_root(&self) -> Option<PathBuf> {
+        match self.mode {
+            Mode::ProcMacro => Some(self.path.join(PROC_MACRO_CRATE).join("lib.rs")),
+            _ => None,
+        }
+    }
+
+    // The source files of the exercise, starting with its crate root
+    pub fn files(&self) -> Vec<PathBuf> {
+        if !self.path.is_dir() {
+            return vec![self.path.clone()];
+        }
+        let root = self.root();
+        let mut files = Vec::new();
+        let mut dirs = vec![self.path.clone()];
+        while let Some(dir) = dirs.pop() {
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for path in entries.flatten().map(|entry| entry.path()) {
+                if path.is_dir() {
+                    dirs.push(path);
+                } else if path.extension().is_some_and(|ext| ext == "rs") && path != root {
+                    files.push(path);
+                }
+            }
+        }
+        files.sort();
+        files.insert(0, root);
+        files
+    }
+
+    // Whether the given file belongs to the exercise
+    pub fn contains(&self, file: &Path) -> bool {
+        file.ancestors().any(|path| path.ends_with(&self.path))
+    }
+
+    // Copy a directory exercise into the scratch directory, so that its
+    // crate root can be extended while its submodules are still found.
+    // Returns where the crate root goes.
+    fn scratch_root(&self, scratch: &ScratchDir) -> PathBuf {
+        if !self.path.is_dir() {
+            return scratch.path.join("exercise.rs");
         }
+        let dir = scratch.path.join("exercise");
+        for file in self.files() {
+            let copy = dir.join(file.strip_prefix(&self.path).expect("Files are in the exercise"));
+            if let Some(parent) = copy.parent() {
+                fs::create_dir_all(parent).expect("Failed to copy the exercise.");
+            }
+            fs::copy(&file, copy).expect("Failed to copy the exercise.");
+        }
+        dir.join(self.root().file_name().expect("The crate root is a file"))
     }
 
     pub fn state(&self) -> State {
-        let mut source_file =
-            File::open(&self.path).expect("We were unable to open the exercise file!");
-
-        let source = {
-            let mut s = String::new();
-            source_file
-                .read_to_string(&mut s)
-                .expect("We were unable to read the exercise file!");
-            s
-        };
+        let re = i_am_done_regex();
 
-        let re = Regex::new(I_AM_DONE_REGEX).unwrap();
+        // The first file of the exercise that still has the comment
+        let source = self
+            .files()
+            .iter()
+            .map(|file| {
+                let mut source_file =
+                    File::open(file).expect("We were unable to open the exercise file!");
+                let mut s = String::new();
+                source_file
+                    .read_to_string(&mut s)
+                    .expect("We were unable to read the exercise file!");
+                s
+            })
+            .find(|source| re.is_match(source));
 
-        if !re.is_match(&source) {
+        let Some(source) = source else {
             return State::Done;
-        }
+        };
 
         let matched_line_index = source
             .lines()
@@ -275,11 +1115,36 @@ path = "{}.rs""#,
     // This is not the best way to check since
     // the user can just remove the "I AM NOT DONE" string from the file
     // without actually having solved anything.
-    // The only other way to truly check this would to compile and run
-    // the exercise; which would be both costly and counterintuitive
+    // Use ProgressDb::is_done to also require that the exercise was
+    // verified to pass.
     pub fn looks_done(&self) -> bool {
         self.state() == State::Done
     }
+
+    // A hash of the exercise's source, used to notice when it changed
+    // since it was last verified.
+    // Every file's path and length are hashed before its contents, so that
+    // moving code from one file to another changes the hash too.
+    // A missing file hashes like an empty one.
+    pub fn hash(&self) -> String {
+        // 64-bit FNV-1a, which is stable across Rust versions and platforms
+        // unlike std's DefaultHasher
+        let fnv = |hash: u64, bytes: &[u8]| {
+            bytes.iter().fold(hash, |hash, &byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+            })
+        };
+        let hash = self.files().iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, file| {
+            let relative = file.strip_prefix(&self.path).unwrap_or(file);
+            let contents = fs::read(file).unwrap_or_default();
+            // Paths can't contain NUL, which ends them
+            let hash = fnv(hash, relative.to_string_lossy().as_bytes());
+            let hash = fnv(hash, &[0]);
+            let hash = fnv(hash, &(contents.len() as u64).to_le_bytes());
+            fnv(hash, &contents)
+        });
+        format!("{hash:016x}")
+    }
 }
 
 impl Display for Exercise {
@@ -288,28 +1153,152 @@ impl Display for Exercise {
     }
 }
 
-#[inline]
-fn clean() {
-    let _ignored = remove_file(&temp_file());
+// The regex matching the `I AM NOT DONE` comment, compiled once
+fn i_am_done_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(I_AM_DONE_REGEX).unwrap())
+}
+
+// The output of a command that ran, as the result of running the exercise:
+// Ok if it exited successfully within its limits
+fn exited((output, success): (ExerciseOutput, bool)) -> Result<ExerciseOutput, ExerciseOutput> {
+    if success {
+        Ok(output)
+    } else {
+        Err(output)
+    }
+}
+
+// The outcome of a compiled exercise that ran successfully, which passes
+// if it printed the expected output, if any
+pub fn check_output(output: ExerciseOutput, expected: Option<&str>) -> Outcome {
+    match expected {
+        Some(expected) if normalize_output(&output.stdout) != expected => {
+            Outcome::WrongOutput(output, expected.to_string())
+        }
+        _ => Outcome::Passed(output),
+    }
+}
+
+// Ignore line endings, trailing whitespace and blank lines around the output
+pub fn normalize_output(output: &str) -> String {
+    let lines: Vec<&str> = output.lines().map(str::trim_end).collect();
+    let start = lines.iter().position(|line| !line.is_empty()).unwrap_or(lines.len());
+    let end = lines.iter().rposition(|line| !line.is_empty()).map_or(start, |end| end + 1);
+    let mut normalized = lines[start..end].join("\n");
+    if !normalized.is_empty() {
+        normalized.push('\n');
+    }
+    normalized
+}
+
+// The outcome of a compiled exercise that didn't run successfully
+fn failed_run(output: ExerciseOutput) -> Outcome {
+    match output.limit {
+        Some(limit) => Outcome::LimitExceeded(limit, output),
+        None => Outcome::RunFailed(output),
+    }
+}
+
+// Whether `cargo miri` can be run, checked once
+fn miri_available() -> bool {
+    static AVAILABLE: OnceLock<bool> = OnceLock::new();
+    *AVAILABLE.get_or_init(|| runs("cargo", &["miri", "--version"]))
+}
+
+// Whether rustfmt can be run, checked once
+fn rustfmt_available() -> bool {
+    static AVAILABLE: OnceLock<bool> = OnceLock::new();
+    *AVAILABLE.get_or_init(|| runs("rustfmt", &["--version"]))
+}
+
+// Whether the given command runs successfully
+fn runs(program: &str, args: &[&str]) -> bool {
+    Command::new(program)
+        .args(args)
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+// The grade of an exercise whose extra tests can't be read
+fn unreadable(what: &str, path: &Path, e: io::Error) -> Grade {
+    Grade::not_run(Outcome::CompileFailed(unreadable_output(what, path, e)), Duration::ZERO, None)
+}
+
+// The output explaining why an extra file of an exercise can't be read
+fn unreadable_output(what: &str, path: &Path, e: io::Error) -> ExerciseOutput {
+    ExerciseOutput::error(format!("Failed to read the {what} {}: {e}\n", path.display()))
+}
+
+// The Cargo.toml of the proc-macro crate with the given crate root and
+// edition
+fn proc_macro_toml(root: &Path, edition: &str) -> String {
+    format!(
+        r#"[package]
+name = "{PROC_MACRO_CRATE}"
+version = "0.0.1"
+edition = "{edition}"
+
+[lib]
+proc-macro = true
+path = "{}""#,
+        manifest_path(root)
+    )
+}
+
+// Render a path for use inside a generated Cargo.toml
+fn manifest_path(path: &Path) -> String {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    path.display().to_string().replace('\\', "/")
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use std::path::Path;
 
     #[test]
     fn test_clean() {
-        File::create(&temp_file()).unwrap();
         let exercise = Exercise {
             name: String::from("example"),
             path: PathBuf::from("tests/fixture/state/pending_exercise.rs"),
             mode: Mode::Compile,
-            hint: String::from(""),
+            ..Default::default()
         };
         let compiled = exercise.compile().unwrap();
+        let scratch = compiled.scratch.path.clone();
+        assert!(compiled.scratch.binary().exists());
         drop(compiled);
-        assert!(!Path::new(&temp_file()).exists());
+        assert!(!scratch.exists());
+    }
+
+    #[test]
+    fn test_concurrent_compilations_are_isolated() {
+        let exercise = Exercise {
+            name: String::from("example"),
+            path: PathBuf::from("tests/fixture/success/compSuccess.rs"),
+            mode: Mode::Compile,
+            ..Default::default()
+        };
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let exercise = Exercise {
+                    name: exercise.name.clone(),
+                    path: exercise.path.clone(),
+                    ..Default::default()
+                };
+                std::thread::spawn(move || {
+                    let compiled = exercise.compile().unwrap();
+                    let scratch = compiled.scratch.path.clone();
+                    compiled.run().unwrap();
+                    scratch
+                })
+            })
+            .collect();
+        let mut scratch_dirs: Vec<PathBuf> =
+            handles.into_iter().map(|h| h.join().unwrap()).collect();
+        scratch_dirs.sort();
+        scratch_dirs.dedup();
+        assert_eq!(scratch_dirs.len(), 4);
     }
 
     #[test]
@@ -318,7 +1307,7 @@ mod test {
             name: "pending_exercise".into(),
             path: PathBuf::from("tests/fixture/state/pending_exercise.rs"),
             mode: Mode::Compile,
-            hint: String::new(),
+            ..Default::default()
         };
 
         let state = exercise.state();
@@ -353,13 +1342,57 @@ mod test {
         assert_eq!(state, State::Pending(expected));
     }
 
+    #[test]
+    fn test_directory_exercise_files() {
+        let exercise = Exercise {
+            name: "garden".into(),
+            path: PathBuf::from("tests/fixture/modules/exercises/garden"),
+            mode: Mode::Compile,
+            ..Default::default()
+        };
+        assert_eq!(
+            exercise.files(),
+            [
+                PathBuf::from("tests/fixture/modules/exercises/garden/main.rs"),
+                PathBuf::from("tests/fixture/modules/exercises/garden/garden/vegetables.rs"),
+                PathBuf::from("tests/fixture/modules/exercises/garden/garden.rs"),
+            ]
+        );
+        let submodule = Path::new("tests/fixture/modules/exercises/garden/garden/vegetables.rs");
+        assert!(exercise.contains(&submodule.canonicalize().unwrap()));
+        assert!(!exercise.contains(Path::new("tests/fixture/modules/exercises/shapes/area.rs")));
+        assert!(!exercise.looks_done());
+    }
+
+    #[test]
+    fn test_hash_tells_files_apart() {
+        let hash = |files: &[(&str, &str)]| {
+            let dir = scratch_dir();
+            fs::create_dir_all(&dir).unwrap();
+            for (name, contents) in files {
+                fs::write(dir.join(name), contents).unwrap();
+            }
+            let exercise = Exercise {
+                name: "split".into(),
+                path: dir.clone(),
+                ..Default::default()
+            };
+            let hash = exercise.hash();
+            fs::remove_dir_all(dir).unwrap();
+            hash
+        };
+        let moved = hash(&[("main.rs", "mod a;\nfn main() {}\n"), ("a.rs", "")]);
+        assert_ne!(moved, hash(&[("main.rs", "mod a;\n"), ("a.rs", "fn main() {}\n")]));
+        assert_eq!(moved, hash(&[("main.rs", "mod a;\nfn main() {}\n"), ("a.rs", "")]));
+    }
+
     #[test]
     fn test_finished_exercise() {
         let exercise = Exercise {
             name: "finished_exercise".into(),
             path: PathBuf::from("tests/fixture/state/finished_exercise.rs"),
             mode: Mode::Compile,
-            hint: String::new(),
+            ..Default::default()
         };
 
         assert_eq!(exercise.state(), State::Done);
@@ -371,9 +1404,106 @@ mod test {
             name: "exercise_with_output".into(),
             path: PathBuf::from("tests/fixture/success/testSuccess.rs"),
             mode: Mode::Test,
-            hint: String::new(),
+            ..Default::default()
         };
         let out = exercise.compile().unwrap().run().unwrap();
         assert!(out.stdout.contains("THIS TEST TOO SHALL PASS"));
     }
+
+    #[test]
+    fn test_count_visible_and_hidden_tests() {
+        let output = "running 3 tests\ntest tests::doubles ... ok\ntest hidden_tests::doubles_negative ... FAILED\ntest hidden_tests::doubles_zero ... ok\n\ntest result: FAILED. 2 passed; 1 failed\n";
+        assert_eq!(
+            TestSummary::parse(output),
+            TestSummary {
+                visible: TestCounts { passed: 1, failed: 0 },
+                hidden: TestCounts { passed: 1, failed: 1 },
+            }
+        );
+    }
+
+    #[test]
+    fn test_normalize_output() {
+        assert_eq!(normalize_output("\n\nHello  \r\n\n  world\t\n\n"), "Hello\n\n  world\n");
+        assert_eq!(normalize_output(" \n\n"), "");
+    }
+
+    #[test]
+    fn test_rustc_args() {
+        let exercise = Exercise {
+            name: "flags".into(),
+            path: PathBuf::from("tests/fixture/flags/exercises/edition.rs"),
+            mode: Mode::Compile,
+            edition: Some("2024".into()),
+            rustc_flags: vec!["-D".into(), "warnings".into()],
+            cfg: vec!["feature=\"loud\"".into()],
+            ..Default::default()
+        };
+        assert_eq!(
+            exercise.rustc_args(),
+            ["--edition", "2024", "--cfg", "feature=\"loud\"", "-D", "warnings"]
+        );
+        assert_eq!(
+            exercise.cargo_rustflags(),
+            Some((
+                "CARGO_ENCODED_RUSTFLAGS",
+                "--cfg\x1ffeature=\"loud\"\x1f-D\x1fwarnings".to_string()
+            ))
+        );
+        let exercise = Exercise {
+            edition: None,
+            rustc_flags: Vec::new(),
+            cfg: Vec::new(),
+            ..exercise
+        };
+        assert_eq!(exercise.rustc_args(), ["--edition", "2021"]);
+        assert_eq!(exercise.cargo_rustflags(), None);
+    }
+
+    #[test]
+    fn test_clippy_builds_the_binary() {
+        let exercise = Exercise {
+            name: String::from("example"),
+            path: PathBuf::from("tests/fixture/success/compSuccess.rs"),
+            mode: Mode::Clippy,
+            ..Default::default()
+        };
+        let compiled = exercise.compile().unwrap();
+        let binary = format!("example{}", env::consts::EXE_SUFFIX);
+        assert!(compiled.scratch.path.join("target/debug").join(binary).exists());
+    }
+
+    #[test]
+    fn test_timeout() {
+        let exercise = Exercise {
+            name: "infinite_loop".into(),
+            path: PathBuf::from("tests/fixture/limits/infiniteLoop.rs"),
+            mode: Mode::Compile,
+            timeout: Some(1),
+            ..Default::default()
+        };
+        // Run without grading, which would need the original version
+        let output = exercise.compile().unwrap().run().unwrap_err();
+        match failed_run(output) {
+            Outcome::LimitExceeded(limit, _) => {
+                assert_eq!(limit, Limit::Timeout(Duration::from_secs(1)))
+            }
+            outcome => panic!("Expected the exercise to time out, got {outcome:?}"),
+        }
+    }
+
+    #[test]
+    fn test_output_limit() {
+        let exercise = Exercise {
+            name: "noisy_output".into(),
+            path: PathBuf::from("tests/fixture/limits/noisyOutput.rs"),
+            mode: Mode::Compile,
+            ..Default::default()
+        };
+        let output = exercise.compile().unwrap().run().unwrap_err();
+        match failed_run(output) {
+            Outcome::LimitExceeded(limit, _) => assert_eq!(limit, Limit::Output),
+            outcome => panic!("Expected the exercise to be stopped, got {outcome:?}"),
+        }
+    }
 }
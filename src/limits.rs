@@ -0,0 +1,263 @@
+use crate::exercise::ExerciseOutput;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+// How long a compiled exercise may run unless info.toml says otherwise
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+// How much address space a compiled exercise may use (Linux only)
+const MEMORY_LIMIT: u64 = 2 * 1024 * 1024 * 1024;
+// How many bytes of stdout and stderr a compiled exercise may write
+const OUTPUT_LIMIT: usize = 4 * 1024 * 1024;
+// How often a running exercise is checked against its limits
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+// A resource limit that a compiled exercise was stopped for
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Limit {
+    // The exercise ran for longer than its timeout
+    Timeout(Duration),
+    // The exercise used more CPU time than allowed
+    CpuTime,
+    // The exercise tried to use more memory than allowed
+    Memory,
+    // The exercise wrote more output than allowed
+    Output,
+}
+
+impl Display for Limit {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Limit::Timeout(timeout) => write!(f, "timed out after {} s", timeout.as_secs_f32()),
+            Limit::CpuTime => write!(f, "exceeded its CPU time limit"),
+            Limit::Memory => write!(f, "exceeded its memory limit of {} MiB", MEMORY_LIMIT >> 20),
+            Limit::Output => write!(f, "exceeded the output limit of {} MiB", OUTPUT_LIMIT >> 20),
+        }
+    }
+}
+
+// Run the given command with its output captured, stopping it when it
+// exceeds the timeout or writes more output than allowed.
+// The command reads the given input, or nothing, from stdin.
+// Everything it starts, like the test binary `cargo test` runs, is stopped
+// along with it and can't keep it running by holding on to its output.
+// Returns the captured output and whether the command exited successfully.
+pub fn run_limited(
+    cmd: &mut Command,
+    timeout: Duration,
+    input: Option<&str>,
+) -> io::Result<(ExerciseOutput, bool)> {
+    run(cmd, timeout, input, false)
+}
+
+// Run a compiled exercise or test binary like `run_limited`, also holding
+// it to the CPU time and memory limits.
+// Tools like cargo and rustdoc aren't, since compiling may well need more.
+pub fn run_compiled(
+    cmd: &mut Command,
+    timeout: Duration,
+    input: Option<&str>,
+) -> io::Result<(ExerciseOutput, bool)> {
+    run(cmd, timeout, input, true)
+}
+
+fn run(
+    cmd: &mut Command,
+    timeout: Duration,
+    input: Option<&str>,
+    rlimited: bool,
+) -> io::Result<(ExerciseOutput, bool)> {
+    let stdin = match input {
+        Some(_) => Stdio::piped(),
+        None => Stdio::null(),
+    };
+    cmd.stdin(stdin)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::unix::process::CommandExt;
+        // A process group of its own, so that everything it starts can be
+        // killed with it
+        cmd.process_group(0);
+        if rlimited {
+            set_rlimits(cmd, timeout);
+        }
+    }
+
+    let mut child = cmd.spawn()?;
+    if let (Some(mut stdin), Some(input)) = (child.stdin.take(), input) {
+        // Written on a separate thread, since the command may write more
+        // output than fits in the pipes before it reads all of its input.
+        // A command that stops early closes stdin, which is not an error.
+        let input = input.to_string();
+        thread::spawn(move || {
+            let _ = stdin.write_all(input.as_bytes());
+        });
+    }
+    let overflowed = Arc::new(AtomicBool::new(false));
+    let stdout = capture(child.stdout.take(), Arc::clone(&overflowed));
+    let stderr = capture(child.stderr.take(), Arc::clone(&overflowed));
+
+    let start = Instant::now();
+    let mut limit = None;
+    let mut exited = None;
+    // Wait for the command to exit and for its output to end, which
+    // processes it started may still be writing to
+    let status = loop {
+        if exited.is_none() {
+            exited = child.try_wait()?;
+        }
+        if let Some(status) = exited {
+            if stdout.is_finished() && stderr.is_finished() {
+                break status;
+            }
+        }
+        if overflowed.load(Ordering::SeqCst) {
+            limit = Some(Limit::Output);
+        } else if start.elapsed() > timeout {
+            limit = Some(Limit::Timeout(timeout));
+        }
+        if limit.is_some() {
+            let killed = kill(&mut child)?;
+            break exited.unwrap_or(killed);
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    // Only what was captured so far once the command was killed
+    let stdout = stdout.text();
+    let stderr = stderr.text();
+    // The command may have exited because its output was no longer read
+    // before the overflow was noticed
+    let overflow = overflowed.load(Ordering::SeqCst).then_some(Limit::Output);
+    let limit = limit
+        .or(overflow)
+        .or_else(|| rlimited.then(|| rlimit_exceeded(&status, &stderr)).flatten());
+    let output = ExerciseOutput {
+        stdout,
+        stderr,
+        limit,
+        diagnostics: Vec::new(),
+    };
+    let success = status.success() && output.limit.is_none();
+    Ok((output, success))
+}
+
+// Kill the child along with everything it started
+fn kill(child: &mut Child) -> io::Result<std::process::ExitStatus> {
+    #[cfg(target_os = "linux")]
+    // SAFETY: `kill` has no memory safety requirements. The child leads its
+    // own process group, whose id is its pid.
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+    // The child may have exited on its own in the meantime
+    let _ignored = child.kill();
+    child.wait()
+}
+
+// Output of a running command, read from one of its pipes
+struct Capture {
+    captured: Arc<Mutex<Vec<u8>>>,
+    reader: JoinHandle<()>,
+}
+
+impl Capture {
+    // Whether the pipe was read to its end
+    fn is_finished(&self) -> bool {
+        self.reader.is_finished()
+    }
+
+    // The output captured so far
+    fn text(&self) -> String {
+        let captured = self.captured.lock().unwrap_or_else(|e| e.into_inner());
+        String::from_utf8_lossy(&captured).to_string()
+    }
+}
+
+// Read a pipe to its end on a separate thread, keeping at most
+// OUTPUT_LIMIT bytes of it and flagging when there was more
+fn capture(pipe: Option<impl Read + Send + 'static>, overflowed: Arc<AtomicBool>) -> Capture {
+    let captured = Arc::new(Mutex::new(Vec::new()));
+    let buffer = Arc::clone(&captured);
+    let reader = thread::spawn(move || {
+        let mut buf = [0; 8192];
+        if let Some(mut pipe) = pipe {
+            while let Ok(n) = pipe.read(&mut buf) {
+                if n == 0 {
+                    break;
+                }
+                let mut captured = buffer.lock().unwrap_or_else(|e| e.into_inner());
+                let room = OUTPUT_LIMIT.saturating_sub(captured.len());
+                captured.extend_from_slice(&buf[..n.min(room)]);
+                if n > room {
+                    overflowed.store(true, Ordering::SeqCst);
+                    break;
+                }
+            }
+        }
+    });
+    Capture { captured, reader }
+}
+
+#[cfg(target_os = "linux")]
+fn set_rlimits(cmd: &mut Command, timeout: Duration) {
+    use std::os::unix::process::CommandExt;
+
+    // CPU time adds up across threads, so a program that keeps every CPU
+    // busy until the timeout must still be stopped by the timeout
+    let cpus = thread::available_parallelism().map_or(1, |n| n.get()) as u64;
+    let cpu_seconds = (timeout.as_secs() + 1) * cpus;
+    let limits = [
+        (libc::RLIMIT_CPU, cpu_seconds, cpu_seconds + 1),
+        (libc::RLIMIT_AS, MEMORY_LIMIT, MEMORY_LIMIT),
+    ];
+    // SAFETY: the closure only calls `getrlimit` and `setrlimit`,
+    // which are async-signal-safe
+    unsafe {
+        cmd.pre_exec(move || {
+            for (resource, soft, hard) in limits {
+                let mut limit = libc::rlimit {
+                    rlim_cur: 0,
+                    rlim_max: 0,
+                };
+                if libc::getrlimit(resource, &mut limit) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                // Never try to raise a limit the environment already set lower
+                let limit = libc::rlimit {
+                    rlim_cur: soft.min(limit.rlim_max),
+                    rlim_max: hard.min(limit.rlim_max),
+                };
+                if libc::setrlimit(resource, &limit) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        });
+    }
+}
+
+// Work out whether the process was stopped by one of its rlimits
+#[cfg(target_os = "linux")]
+fn rlimit_exceeded(status: &std::process::ExitStatus, stderr: &str) -> Option<Limit> {
+    use std::os::unix::process::ExitStatusExt;
+
+    match status.signal() {
+        // SIGKILL once it went on past SIGXCPU to the hard limit, since
+        // nothing else but `kill` above sends it, which sets the limit itself
+        Some(libc::SIGXCPU | libc::SIGKILL) => Some(Limit::CpuTime),
+        Some(libc::SIGABRT) if stderr.contains("memory allocation of") => Some(Limit::Memory),
+        _ => None,
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn rlimit_exceeded(_status: &std::process::ExitStatus, _stderr: &str) -> Option<Limit> {
+    None
+}
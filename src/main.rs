@@ -1,7 +1,14 @@
-use crate::exercise::{Exercise, ExerciseList};
+use crate::bench::Benchmark;
+use crate::diff::{diff, stat};
+use crate::events::{emit, emit_grade, emit_started, Event};
+use crate::exercise::{Exercise, ExerciseList, GradeOptions, Outcome, TestSummary};
+use crate::progress::ProgressDb;
 use crate::project::RustAnalyzerProject;
-use crate::run::{reset, run};
-use crate::verify::verify;
+use crate::report::{Report, ReportSpec};
+use crate::run::{reset, run, undo_reset};
+use crate::scheduler::{default_jobs, schedule};
+use crate::ui::{json_output, set_format, Format};
+use crate::verify::{report_failure, report_seed, verify};
 use argh::FromArgs;
 use console::Emoji;
 use notify::DebouncedEvent;
@@ -10,7 +17,8 @@ use serde::{Deserialize, Serialize};
 use std::ffi::OsStr;
 use std::fs;
 use std::io::{self, prelude::*};
-use std::path::Path;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{channel, RecvTimeoutError};
@@ -22,9 +30,22 @@ use std::time::{UNIX_EPOCH, SystemTime};
 #[macro_use]
 mod ui;
 
+mod allocations;
+mod bench;
+mod cargo;
+mod diagnostics;
+mod diff;
+mod events;
 mod exercise;
+mod limits;
+mod pristine;
+mod progress;
 mod project;
+mod property;
+mod report;
 mod run;
+mod scheduler;
+mod tamper;
 mod verify;
 
 // In sync with crate version
@@ -39,6 +60,10 @@ struct Args {
     /// show the executable version
     #[argh(switch, short = 'v')]
     version: bool,
+    /// output format of verify, cicvverify, run, list and hint: `human` (default)
+    /// or `json` for one JSON event per line
+    #[argh(option, default = "Format::Human")]
+    format: Format,
     #[argh(subcommand)]
     nested: Option<Subcommands>,
 }
@@ -53,17 +78,46 @@ enum Subcommands {
     Hint(HintArgs),
     List(ListArgs),
     Lsp(LspArgs),
+    Diff(DiffArgs),
     CicvVerify(CicvVerifyArgs)
 }
 
 #[derive(FromArgs, PartialEq, Debug)]
 #[argh(subcommand, name = "cicvverify", description = "cicvverify")]
-struct CicvVerifyArgs {}
+struct CicvVerifyArgs {
+    #[argh(option, short = 'j')]
+    /// number of exercises to grade at the same time
+    /// (defaults to the number of CPUs)
+    jobs: Option<usize>,
+    #[argh(option)]
+    /// write a report as `junit=<path>` or `tap=<path>`, can be repeated
+    report: Vec<ReportSpec>,
+    #[argh(option)]
+    /// a checkout of the original course, to detect modified tests
+    /// (defaults to the first commit of the git repository, or the versions
+    /// recorded the first time rustlings ran)
+    pristine: Option<PathBuf>,
+    #[argh(option, default = "PathBuf::from(\".\")")]
+    /// the directory the `hidden_tests` paths in info.toml are relative to
+    /// (defaults to the current directory)
+    hidden_tests: PathBuf,
+    #[argh(option)]
+    /// the seed for the property tests (defaults to a random one per exercise)
+    seed: Option<u64>,
+}
 
 #[derive(FromArgs, PartialEq, Debug)]
 #[argh(subcommand, name = "verify")]
 /// Verifies all exercises according to the recommended order
-struct VerifyArgs {}
+struct VerifyArgs {
+    #[argh(option, short = 'j')]
+    /// number of exercises to compile and run ahead at the same time
+    /// (defaults to the number of CPUs)
+    jobs: Option<usize>,
+    #[argh(option)]
+    /// write a report as `junit=<path>` or `tap=<path>`, can be repeated
+    report: Vec<ReportSpec>,
+}
 
 #[derive(FromArgs, PartialEq, Debug)]
 #[argh(subcommand, name = "watch")]
@@ -81,15 +135,25 @@ struct RunArgs {
     #[argh(positional)]
     /// the name of the exercise
     name: String,
+    #[argh(option)]
+    /// the seed for the property tests, to check the same inputs as
+    /// a previous run
+    seed: Option<u64>,
 }
 
 #[derive(FromArgs, PartialEq, Debug)]
 #[argh(subcommand, name = "reset")]
-/// Resets a single exercise using "git stash -- <filename>"
+/// Resets exercises to their original version, keeping a backup of yours
 struct ResetArgs {
     #[argh(positional)]
-    /// the name of the exercise
-    name: String,
+    /// the name of the exercise, or of a topic to reset all of its exercises
+    name: Option<String>,
+    #[argh(switch)]
+    /// reset all exercises
+    all: bool,
+    #[argh(switch)]
+    /// restore the versions replaced by the last reset
+    undo: bool,
 }
 
 #[derive(FromArgs, PartialEq, Debug)]
@@ -106,6 +170,21 @@ struct HintArgs {
 /// Enable rust-analyzer for exercises
 struct LspArgs {}
 
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand, name = "diff")]
+/// Shows how exercises were changed compared to their original version
+struct DiffArgs {
+    #[argh(positional)]
+    /// the name of the exercise, or of a topic to show all of its exercises
+    name: Option<String>,
+    #[argh(switch)]
+    /// show all exercises
+    all: bool,
+    #[argh(switch)]
+    /// only show how many lines were changed, per topic
+    stat: bool,
+}
+
 #[derive(FromArgs, PartialEq, Debug)]
 #[argh(subcommand, name = "list")]
 /// Lists the exercises available in Rustlings
@@ -138,7 +217,35 @@ pub struct ExerciseCheckList {
 #[derive(Deserialize, Serialize)]
 pub struct ExerciseResult {
     pub name: String,
-    pub result: bool
+    pub result: bool,
+    pub status: ExerciseStatus,
+    // The results of the visible and the hidden tests, for test exercises
+    // with hidden tests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tests: Option<TestSummary>,
+    // The seed the property tests ran with, for exercises with property tests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    // The measured running times, for exercises with a benchmark
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bench: Option<Benchmark>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExerciseStatus {
+    Passed,
+    Failed,
+    // The test module or a locked region was changed, so the exercise
+    // wasn't graded
+    TestsModified,
+    // The tests passed, but the running time grows faster than allowed
+    TooSlow,
+    // The exercise couldn't be graded, like a Miri exercise without Miri
+    Skipped,
+    // The exercise was stopped for running too long or exceeding another
+    // resource limit
+    LimitExceeded,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -146,12 +253,18 @@ pub struct  ExerciseStatistics {
     pub total_exercations: usize,
     pub total_succeeds: usize,
     pub total_failures: usize,
+    #[serde(default)]
+    pub total_tests_modified: usize,
+    #[serde(default)]
+    pub total_skipped: usize,
+    #[serde(default)]
+    pub total_limit_exceeded: usize,
     pub total_time: u32,
 }
 
-#[tokio::main]
-async fn main() {
+fn main() {
     let args: Args = argh::from_env();
+    set_format(args.format);
 
     if args.version {
         println!("v{VERSION}");
@@ -163,32 +276,51 @@ async fn main() {
     }
 
     if !Path::new("info.toml").exists() {
-        println!(
+        let message = format!(
             "{} must be run from the rustlings directory",
             std::env::current_exe().unwrap().to_str().unwrap()
         );
-        println!("Try `cd rustlings/`!");
+        if json_output() {
+            emit(&Event::Error { message: &message });
+        } else {
+            println!("{message}");
+            println!("Try `cd rustlings/`!");
+        }
         std::process::exit(1);
     }
 
     if !rustc_exists() {
-        println!("We cannot find `rustc`.");
-        println!("Try running `rustc --version` to diagnose your problem.");
-        println!("For instructions on how to install Rust, check the README.");
+        if json_output() {
+            emit(&Event::Error {
+                message: "We cannot find `rustc`.",
+            });
+        } else {
+            println!("We cannot find `rustc`.");
+            println!("Try running `rustc --version` to diagnose your problem.");
+            println!("For instructions on how to install Rust, check the README.");
+        }
         std::process::exit(1);
     }
 
     let toml_str = &fs::read_to_string("info.toml").unwrap();
     let exercises = toml::from_str::<ExerciseList>(toml_str).unwrap().exercises;
     let verbose = args.nocapture;
+    let mut db = ProgressDb::load();
 
     let command = args.nested.unwrap_or_else(|| {
         println!("{DEFAULT_OUT}\n");
         std::process::exit(0);
     });
+    // cicvverify grades the exercises as the learner submitted them, which
+    // must never be taken for the original versions
+    if !matches!(command, Subcommands::CicvVerify(_)) {
+        if let Err(e) = pristine::record(&exercises) {
+            warn!("Failed to record the original version of the exercises: {}", e);
+        }
+    }
     match command {
         Subcommands::List(subargs) => {
-            if !subargs.paths && !subargs.names {
+            if !subargs.paths && !subargs.names && !json_output() {
                 println!("{:<17}\t{:<46}\t{:<7}", "Name", "Path", "Status");
             }
             let mut exercises_done: u16 = 0;
@@ -198,19 +330,29 @@ async fn main() {
                 let filter_cond = filters
                     .split(',')
                     .filter(|f| !f.trim().is_empty())
-                    .any(|f| e.name.contains(&f) || fname.contains(&f));
-                let status = if e.looks_done() {
+                    .any(|f| e.name.contains(f) || fname.contains(f));
+                let done = db.is_done(e);
+                let status = if done {
                     exercises_done += 1;
                     "Done"
                 } else {
                     "Pending"
                 };
                 let solve_cond = {
-                    (e.looks_done() && subargs.solved)
-                        || (!e.looks_done() && subargs.unsolved)
+                    (done && subargs.solved)
+                        || (!done && subargs.unsolved)
                         || (!subargs.solved && !subargs.unsolved)
                 };
                 if solve_cond && (filter_cond || subargs.filter.is_none()) {
+                    if json_output() {
+                        emit(&Event::Exercise {
+                            name: &e.name,
+                            path: &e.path,
+                            mode: e.mode,
+                            done,
+                        });
+                        return;
+                    }
                     let line = if subargs.paths {
                         format!("{fname}\n")
                     } else if subargs.names {
@@ -233,6 +375,13 @@ async fn main() {
                     }
                 }
             });
+            if json_output() {
+                emit(&Event::Progress {
+                    done: exercises_done as usize,
+                    total: exercises.len(),
+                });
+                std::process::exit(0);
+            }
             let percentage_progress = exercises_done as f32 / exercises.len() as f32 * 100.0;
             println!(
                 "Progress: You completed {} / {} exercises ({:.1} %).",
@@ -244,101 +393,216 @@ async fn main() {
         }
 
         Subcommands::Run(subargs) => {
-            let exercise = find_exercise(&subargs.name, &exercises);
-            run(exercise, verbose).unwrap_or_else(|_| std::process::exit(1));
+            let exercise = find_exercise(&subargs.name, &exercises, &db);
+            let result = run(exercise, verbose, subargs.seed);
+            db.record(exercise, result.is_ok());
+            db.save();
+            result.unwrap_or_else(|_| std::process::exit(1));
         }
 
         Subcommands::Reset(subargs) => {
-            let exercise = find_exercise(&subargs.name, &exercises);
-
-            reset(exercise).unwrap_or_else(|_| std::process::exit(1));
+            let result = if subargs.undo {
+                undo_reset()
+            } else if subargs.all {
+                reset(&exercises.iter().collect::<Vec<_>>())
+            } else if let Some(name) = &subargs.name {
+                reset(&find_exercises(name, &exercises, &db))
+            } else {
+                eprintln!("Name an exercise or a topic to reset, or use --all or --undo.");
+                Err(())
+            };
+            result.unwrap_or_else(|_| std::process::exit(1));
         }
 
         Subcommands::Hint(subargs) => {
-            let exercise = find_exercise(&subargs.name, &exercises);
+            let exercise = find_exercise(&subargs.name, &exercises, &db);
 
-            println!("{}", exercise.hint);
+            if json_output() {
+                emit(&Event::Hint {
+                    exercise: &exercise.name,
+                    hint: &exercise.hint,
+                });
+            } else {
+                println!("{}", exercise.hint);
+            }
         }
 
-        Subcommands::Verify(_subargs) => {
-            verify(&exercises, (0, exercises.len()), verbose, false)
+        Subcommands::Verify(subargs) => {
+            let jobs = subargs.jobs.unwrap_or_else(default_jobs);
+            verify(
+                &exercises,
+                (0, exercises.len()),
+                verbose,
+                false,
+                jobs,
+                &subargs.report,
+                &mut db,
+            )
                 .unwrap_or_else(|_| std::process::exit(1));
         }
 
-        Subcommands::CicvVerify(_subargs) => {
-            // let toml_str = &fs::read_to_string("info.toml").unwrap();
-            // exercises = toml::from_str::<ExerciseList>(toml_str).unwrap().exercises;
+        Subcommands::CicvVerify(subargs) => {
             let now_start = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-            let rights = Arc::new(Mutex::new(0));
+            let jobs = subargs.jobs.unwrap_or_else(default_jobs);
             let alls = exercises.len();
+            let mut rights = 0;
+            let mut report = Report::new();
+            match subargs.pristine {
+                Some(dir) => pristine::set_originals_dir(dir),
+                None if !pristine::available() => {
+                    note!("The original version of the exercises isn't available, so modified tests can't be detected.");
+                    note!("Pass a checkout of the original course with `--pristine <dir>`.");
+                }
+                None => {}
+            }
 
-            let exercise_check_list =  Arc::new(Mutex::new(
-                ExerciseCheckList {
-                    exercises: vec![], 
-                    user_name:  None, 
-                    statistics: ExerciseStatistics { 
-                        total_exercations: alls, 
-                        total_succeeds: 0, 
-                        total_failures: 0, 
-                        total_time: 0, 
+            let mut exercise_check_list = ExerciseCheckList {
+                exercises: vec![],
+                user_name: None,
+                statistics: ExerciseStatistics {
+                    total_exercations: alls,
+                    total_succeeds: 0,
+                    total_failures: 0,
+                    total_tests_modified: 0,
+                    total_skipped: 0,
+                    total_limit_exceeded: 0,
+                    total_time: 0,
+                },
+            };
+
+            let options = GradeOptions {
+                hidden_tests_dir: Some(subargs.hidden_tests),
+                seed: subargs.seed,
+            };
+            let grade = |exercise: &Exercise| {
+                if json_output() {
+                    emit_started(exercise);
+                }
+                exercise.grade_with(&options)
+            };
+            schedule(&exercises, jobs, grade, |index, grade, elapsed| {
+                let exercise = &exercises[index];
+                report.add(index, exercise, &grade);
+                let result = grade.outcome.passed();
+                let status = match grade.outcome {
+                    Outcome::Passed(_) => ExerciseStatus::Passed,
+                    Outcome::TestsModified(_) => ExerciseStatus::TestsModified,
+                    Outcome::TooSlow(..) => ExerciseStatus::TooSlow,
+                    Outcome::Skipped(_) => ExerciseStatus::Skipped,
+                    Outcome::LimitExceeded(..) => ExerciseStatus::LimitExceeded,
+                    _ => ExerciseStatus::Failed,
+                };
+                let statistics = &mut exercise_check_list.statistics;
+                match status {
+                    ExerciseStatus::Passed => {
+                        rights += 1;
+                        statistics.total_succeeds += 1;
+                    }
+                    ExerciseStatus::TestsModified => {
+                        statistics.total_failures += 1;
+                        statistics.total_tests_modified += 1;
                     }
+                    ExerciseStatus::Skipped => statistics.total_skipped += 1,
+                    ExerciseStatus::LimitExceeded => {
+                        statistics.total_failures += 1;
+                        statistics.total_limit_exceeded += 1;
+                    }
+                    ExerciseStatus::TooSlow | ExerciseStatus::Failed => statistics.total_failures += 1,
                 }
-            ));
-
-            let mut tasks = vec![];
-            for exercise in exercises {
-                let now_start = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-                let inner_exercise = exercise;
-                let c_mutex = Arc::clone(&rights);
-                let exercise_check_list_ref = Arc::clone(&exercise_check_list);
-                let _verbose = verbose.clone();
-                let t = tokio::task::spawn( async move {
-                    match run(&inner_exercise, true) {
-                    // match verify(vec![&inner_exercise], (0, 1), true, true) {
-                        Ok(_) => {
-                            *c_mutex.lock().unwrap() += 1;
-                            println!("{}执行成功", inner_exercise.name);
-                            println!("总的题目数: {}", alls);
-                            println!("当前做正确的题目数: {}", *c_mutex.lock().unwrap());
-                            let now_end = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-                            println!("当前修改试卷耗时: {} s", now_end - now_start);
-                            exercise_check_list_ref.lock().unwrap().exercises.push(ExerciseResult{ 
-                                name: inner_exercise.name, result: true,
-                            });
-                            exercise_check_list_ref.lock().unwrap().statistics.total_succeeds += 1;
-                        },
-                        Err(_) => {
-                            println!("{}执行失败", inner_exercise.name);
-                            println!("总的题目数: {}", alls);
-                            println!("当前做正确的题目数: {}", *c_mutex.lock().unwrap());
-                            let now_end = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-                            println!("当前修改试卷耗时: {} s", now_end - now_start);
-                            exercise_check_list_ref.lock().unwrap().exercises.push(ExerciseResult{ 
-                                name: inner_exercise.name, result: false,
-                            });
-                            exercise_check_list_ref.lock().unwrap().statistics.total_failures += 1;
+                let tests = grade.tests.filter(|_| exercise.hidden_tests.is_some());
+                if json_output() {
+                    emit_grade(exercise, &grade);
+                    emit(&Event::Progress {
+                        done: rights,
+                        total: alls,
+                    });
+                } else {
+                    if result {
+                        println!("{}执行成功", exercise.name);
+                    } else if status == ExerciseStatus::TestsModified {
+                        report_failure(exercise, &grade.outcome);
+                        println!("{}测试被修改", exercise.name);
+                    } else if status == ExerciseStatus::Skipped {
+                        report_failure(exercise, &grade.outcome);
+                        println!("{}已跳过", exercise.name);
+                    } else if let Outcome::LimitExceeded(limit, _) = &grade.outcome {
+                        if verbose {
+                            report_failure(exercise, &grade.outcome);
+                        }
+                        println!("{}超出资源限制: {}", exercise.name, limit);
+                    } else {
+                        if verbose {
+                            report_failure(exercise, &grade.outcome);
+                        }
+                        if let (Outcome::RunFailed(_), Some(seed)) = (&grade.outcome, grade.seed) {
+                            report_seed(exercise, seed);
                         }
+                        println!("{}执行失败", exercise.name);
+                    }
+                    if let Some(tests) = tests {
+                        println!(
+                            "{}可见测试通过: {}/{}, 隐藏测试通过: {}/{}",
+                            exercise.name,
+                            tests.visible.passed,
+                            tests.visible.passed + tests.visible.failed,
+                            tests.hidden.passed,
+                            tests.hidden.passed + tests.hidden.failed
+                        );
+                    }
+                    if let Some(benchmark) = &grade.bench {
+                        println!(
+                            "{}运行时间增长率: n^{:.2}, 上界: {}",
+                            exercise.name, benchmark.exponent, benchmark.bound
+                        );
                     }
+                    println!("总的题目数: {}", alls);
+                    println!("当前做正确的题目数: {}", rights);
+                    println!("当前修改试卷耗时: {} s", elapsed.as_secs());
+                }
+                exercise_check_list.exercises.push(ExerciseResult {
+                    name: exercise.name.clone(),
+                    result,
+                    status,
+                    tests,
+                    seed: grade.seed,
+                    bench: grade.bench,
                 });
-                tasks.push(t);
-            }
-            for task in tasks { task.await.unwrap(); }
+                ControlFlow::Continue(())
+            });
+
             let now_end = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
             let total_time = now_end - now_start;
-            println!("===============================试卷批改完成,总耗时: {} s; ==================================", total_time);
-            let exercise_check_list_ref = Arc::clone(&exercise_check_list);
-            exercise_check_list_ref.lock().unwrap().statistics.total_time = total_time as u32;
-            let serialized = serde_json::to_string_pretty(&*exercise_check_list.lock().unwrap()).unwrap();
+            note!("===============================试卷批改完成,总耗时: {} s; ==================================", total_time);
+            exercise_check_list.statistics.total_time = total_time as u32;
+            let serialized = serde_json::to_string_pretty(&exercise_check_list).unwrap();
+            report.write_all(&subargs.report);
             fs::write(".github/result/check_result.json", serialized).unwrap();
         },
 
+        Subcommands::Diff(subargs) => {
+            let selected = if subargs.all {
+                exercises.iter().collect()
+            } else if let Some(name) = &subargs.name {
+                find_exercises(name, &exercises, &db)
+            } else {
+                eprintln!("Name an exercise or a topic to show, or use --all.");
+                std::process::exit(1);
+            };
+            let result = if subargs.stat {
+                stat(&selected)
+            } else {
+                diff(&selected)
+            };
+            result.unwrap_or_else(|_| std::process::exit(1));
+        }
+
         Subcommands::Lsp(_subargs) => {
             let mut project = RustAnalyzerProject::new();
             project
                 .get_sysroot_src()
                 .expect("Couldn't find toolchain path, do you have `rustc` installed?");
             project
-                .exercises_to_json()
+                .exercises_to_json(&exercises)
                 .expect("Couldn't parse rustlings exercises files");
 
             if project.crates.is_empty() {
@@ -351,7 +615,7 @@ async fn main() {
             }
         }
 
-        Subcommands::Watch(_subargs) => match watch(&exercises, verbose, _subargs.success_hints) {
+        Subcommands::Watch(_subargs) => match watch(&exercises, verbose, _subargs.success_hints, &mut db) {
             Err(e) => {
                 println!(
                     "Error: Could not watch your progress. Error message was {:?}.",
@@ -421,14 +685,20 @@ fn spawn_watch_shell(
     });
 }
 
-fn find_exercise<'a>(name: &str, exercises: &'a [Exercise]) -> &'a Exercise {
+fn find_exercise<'a>(name: &str, exercises: &'a [Exercise], db: &ProgressDb) -> &'a Exercise {
     if name.eq("next") {
         exercises
             .iter()
-            .find(|e| !e.looks_done())
+            .find(|e| !db.is_done(e))
             .unwrap_or_else(|| {
-                println!("🎉 Congratulations! You have done all the exercises!");
-                println!("🔚 There are no more exercises to do next!");
+                if json_output() {
+                    emit(&Event::Error {
+                        message: "There are no more exercises to do next!",
+                    });
+                } else {
+                    println!("🎉 Congratulations! You have done all the exercises!");
+                    println!("🔚 There are no more exercises to do next!");
+                }
                 std::process::exit(1)
             })
     } else {
@@ -436,12 +706,31 @@ fn find_exercise<'a>(name: &str, exercises: &'a [Exercise]) -> &'a Exercise {
             .iter()
             .find(|e| e.name == name)
             .unwrap_or_else(|| {
-                println!("No exercise found for '{name}'!");
+                let message = format!("No exercise found for '{name}'!");
+                if json_output() {
+                    emit(&Event::Error { message: &message });
+                } else {
+                    println!("{message}");
+                }
                 std::process::exit(1)
             })
     }
 }
 
+// Find the exercise with the given name, or all exercises of the topic
+// with that name
+fn find_exercises<'a>(name: &str, exercises: &'a [Exercise], db: &ProgressDb) -> Vec<&'a Exercise> {
+    let topic: Vec<&Exercise> = exercises
+        .iter()
+        .filter(|e| e.path.parent().and_then(Path::file_name) == Some(OsStr::new(name)))
+        .collect();
+    if topic.is_empty() || exercises.iter().any(|e| e.name == name) {
+        vec![find_exercise(name, exercises, db)]
+    } else {
+        topic
+    }
+}
+
 enum WatchStatus {
     Finished,
     Unfinished,
@@ -451,6 +740,7 @@ fn watch(
     exercises: &[Exercise],
     verbose: bool,
     success_hints: bool,
+    db: &mut ProgressDb,
 ) -> notify::Result<WatchStatus> {
     /* Clears the terminal with an ANSI escape code.
     Works in UNIX and newer Windows terminals. */
@@ -472,6 +762,9 @@ fn watch(
         (0, exercises.len()),
         verbose,
         success_hints,
+        1,
+        &[],
+        db,
     ) {
         Ok(_) => return Ok(WatchStatus::Finished),
         Err(exercise) => Arc::new(Mutex::new(Some(to_owned_hint(exercise)))),
@@ -480,31 +773,35 @@ fn watch(
     loop {
         match rx.recv_timeout(Duration::from_secs(1)) {
             Ok(event) => match event {
-                DebouncedEvent::Create(b) | DebouncedEvent::Chmod(b) | DebouncedEvent::Write(b) => {
-                    if b.extension() == Some(OsStr::new("rs")) && b.exists() {
-                        let filepath = b.as_path().canonicalize().unwrap();
-                        let pending_exercises = exercises
-                            .iter()
-                            .find(|e| filepath.ends_with(&e.path))
-                            .into_iter()
-                            .chain(
-                                exercises
-                                    .iter()
-                                    .filter(|e| !e.looks_done() && !filepath.ends_with(&e.path)),
-                            );
-                        let num_done = exercises.iter().filter(|e| e.looks_done()).count();
-                        clear_screen();
-                        match verify(
-                            pending_exercises,
-                            (num_done, exercises.len()),
-                            verbose,
-                            success_hints,
-                        ) {
-                            Ok(_) => return Ok(WatchStatus::Finished),
-                            Err(exercise) => {
-                                let mut failed_exercise_hint = failed_exercise_hint.lock().unwrap();
-                                *failed_exercise_hint = Some(to_owned_hint(exercise));
-                            }
+                DebouncedEvent::Create(b) | DebouncedEvent::Chmod(b) | DebouncedEvent::Write(b)
+                    if b.extension() == Some(OsStr::new("rs")) && b.exists() =>
+                {
+                    let filepath = b.as_path().canonicalize().unwrap();
+                    let pending_exercises = exercises
+                        .iter()
+                        .find(|e| e.contains(&filepath))
+                        .into_iter()
+                        .chain(
+                            exercises
+                                .iter()
+                                .filter(|e| !db.is_done(e) && !e.contains(&filepath)),
+                        )
+                        .collect::<Vec<_>>();
+                    let num_done = exercises.iter().filter(|e| db.is_done(e)).count();
+                    clear_screen();
+                    match verify(
+                        pending_exercises,
+                        (num_done, exercises.len()),
+                        verbose,
+                        success_hints,
+                        1,
+                        &[],
+                        db,
+                    ) {
+                        Ok(_) => return Ok(WatchStatus::Finished),
+                        Err(exercise) => {
+                            let mut failed_exercise_hint = failed_exercise_hint.lock().unwrap();
+                            *failed_exercise_hint = Some(to_owned_hint(exercise));
                         }
                     }
                 }
@@ -524,7 +821,7 @@ fn watch(
 
 fn rustc_exists() -> bool {
     Command::new("rustc")
-        .args(&["--version"])
+        .args(["--version"])
         .stdout(Stdio::null())
         .spawn()
         .and_then(|mut child| child.wait())
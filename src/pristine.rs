@@ -0,0 +1,268 @@
+use crate::exercise::Exercise;
+use console::style;
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+
+// Where rustlings keeps its data, relative to the rustlings directory
+const DATA_DIR: &str = ".rustlings";
+// Overrides DATA_DIR, mostly useful for tests
+const DATA_DIR_ENV: &str = "RUSTLINGS_DATA_DIR";
+// Where the original version of every exercise is kept in DATA_DIR when
+// the course isn't a git repository
+const PRISTINE_DIR: &str = "pristine";
+// Where `rustlings reset` keeps the versions it replaced in DATA_DIR,
+// until `rustlings reset --undo` restores them
+const BACKUP_DIR: &str = "backup";
+
+// A checkout of the course to read the original versions from instead
+// of the git repository or PRISTINE_DIR
+static ORIGINALS_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+pub fn set_originals_dir(dir: PathBuf) {
+    let _ = ORIGINALS_DIR.set(dir);
+}
+
+// Where the original versions of the exercises are read from
+enum Originals {
+    // A checkout of the course, or the versions recorded in PRISTINE_DIR
+    Dir(PathBuf),
+    // The first commit of the git repository of the course, which is the
+    // course as it was handed out
+    Git(&'static GitCommit),
+}
+
+struct GitCommit {
+    id: String,
+    files: HashSet<String>,
+}
+
+// The original versions of the exercises, if any are available
+fn originals() -> Option<Originals> {
+    if let Some(dir) = ORIGINALS_DIR.get() {
+        return Some(Originals::Dir(dir.clone()));
+    }
+    if let Some(commit) = first_commit() {
+        return Some(Originals::Git(commit));
+    }
+    let recorded = data_dir().join(PRISTINE_DIR);
+    recorded.is_dir().then_some(Originals::Dir(recorded))
+}
+
+// Whether the original versions of the exercises are available
+pub fn available() -> bool {
+    originals().is_some()
+}
+
+// The first commit of the git repository the course is the top level of.
+// A shallow clone doesn't have it, and only has the latest commit instead.
+fn first_commit() -> Option<&'static GitCommit> {
+    static FIRST_COMMIT: OnceLock<Option<GitCommit>> = OnceLock::new();
+    FIRST_COMMIT
+        .get_or_init(|| {
+            let repository = git(&["rev-parse", "--show-toplevel", "--is-shallow-repository"])?;
+            let repository = String::from_utf8_lossy(&repository).to_string();
+            let (top_level, shallow) = repository.trim_end().split_once('\n')?;
+            let current_dir = env::current_dir().and_then(fs::canonicalize).ok()?;
+            if fs::canonicalize(top_level).ok()? != current_dir || shallow != "false" {
+                return None;
+            }
+            let roots = git(&["rev-list", "--max-parents=0", "HEAD"])?;
+            let id = String::from_utf8_lossy(&roots).lines().last()?.to_string();
+            let files = git(&["ls-tree", "-r", "-z", "--name-only", &id])?
+                .split(|&byte| byte == 0)
+                .filter(|name| !name.is_empty())
+                .map(|name| String::from_utf8_lossy(name).to_string())
+                .collect();
+            Some(GitCommit { id, files })
+        })
+        .as_ref()
+}
+
+// The output of a successful git command
+fn git(args: &[&str]) -> Option<Vec<u8>> {
+    let output = Command::new("git").args(args).output().ok()?;
+    output.status.success().then_some(output.stdout)
+}
+
+// Record the current version of every exercise that wasn't recorded yet,
+// unless the original versions are in git.
+// This happens the first time rustlings runs, before the learner changed
+// anything, so the recorded versions are hopefully the original ones.
+// A directory exercise is recorded as a whole, so that the files the
+// learner adds to it later are never taken for original ones.
+pub fn record(exercises: &[Exercise]) -> io::Result<()> {
+    if first_commit().is_some() {
+        return Ok(());
+    }
+    let mut warned = false;
+    for exercise in exercises {
+        if pristine_path(&exercise.path).exists() || !exercise.path.exists() {
+            continue;
+        }
+        // On stderr, so that the output of the command stays the same
+        if !warned {
+            eprintln!(
+                "{} {}",
+                style("!").red(),
+                style("The course isn't a git repository, so the exercises as they are now are kept as their original version").red()
+            );
+            warned = true;
+        }
+        for file in exercise.files() {
+            let pristine = pristine_path(&file);
+            if let Some(parent) = pristine.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(&file, pristine)?;
+        }
+    }
+    Ok(())
+}
+
+// The original version of a file of an exercise
+pub fn original(file: &Path) -> io::Result<Vec<u8>> {
+    match originals() {
+        Some(Originals::Dir(dir)) => fs::read(dir.join(file)),
+        Some(Originals::Git(commit)) => {
+            let object = format!("{}:{}", commit.id, git_path(file));
+            git(&["cat-file", "blob", &object]).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::NotFound, "it isn't in the first commit")
+            })
+        }
+        None => Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "the original exercises aren't available",
+        )),
+    }
+}
+
+// Whether the file has an original version
+pub fn has_original(file: &Path) -> bool {
+    match originals() {
+        Some(Originals::Dir(dir)) => dir.join(file).exists(),
+        Some(Originals::Git(commit)) => commit.files.contains(&git_path(file)),
+        None => false,
+    }
+}
+
+// The path of a file of the course as git names it, relative to the top
+// level of the repository
+fn git_path(file: &Path) -> String {
+    let components: Vec<_> = file
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy()),
+            _ => None,
+        })
+        .collect();
+    components.join("/")
+}
+
+// Whether the learner added the file to a directory exercise: the
+// original version of the exercise is known but doesn't have the file
+pub fn added(exercise: &Exercise, file: &Path) -> bool {
+    let root = exercise.root();
+    exercise.path.is_dir()
+        && file != root
+        && has_original(&root)
+        && !has_original(file)
+}
+
+// The files of the exercise which were changed or added since they were
+// recorded
+fn modified_files(exercise: &Exercise) -> io::Result<Vec<PathBuf>> {
+    let mut modified = Vec::new();
+    for file in exercise.files() {
+        if added(exercise, &file) || fs::read(&file)? != original(&file)? {
+            modified.push(file);
+        }
+    }
+    Ok(modified)
+}
+
+// Replace the given exercises with their original versions, removing the
+// files the learner added.
+// The replaced versions are kept in place of those of the previous reset.
+// Returns the exercises that were actually changed.
+pub fn restore<'a>(exercises: &[&'a Exercise]) -> io::Result<Vec<&'a Exercise>> {
+    let mut modified = Vec::new();
+    for &exercise in exercises {
+        let files = modified_files(exercise)?;
+        if !files.is_empty() {
+            modified.push((exercise, files));
+        }
+    }
+    if modified.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let backup_dir = data_dir().join(BACKUP_DIR);
+    if backup_dir.exists() {
+        fs::remove_dir_all(backup_dir)?;
+    }
+    for (exercise, files) in &modified {
+        for file in files {
+            let backup = backup_path(file);
+            if let Some(parent) = backup.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(file, backup)?;
+            if added(exercise, file) {
+                fs::remove_file(file)?;
+            } else {
+                fs::write(file, original(file)?)?;
+            }
+        }
+    }
+    Ok(modified.into_iter().map(|(exercise, _)| exercise).collect())
+}
+
+// Put back the versions replaced by the last reset.
+// Returns the paths of the restored files, which is empty if there was
+// nothing to undo.
+pub fn undo() -> io::Result<Vec<PathBuf>> {
+    let backup_dir = data_dir().join(BACKUP_DIR);
+    let mut restored = Vec::new();
+    if !backup_dir.exists() {
+        return Ok(restored);
+    }
+    let mut dirs = vec![backup_dir.clone()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            let target = path
+                .strip_prefix(&backup_dir)
+                .expect("Backups live in the backup directory")
+                .to_path_buf();
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(&path, &target)?;
+            restored.push(target);
+        }
+    }
+    fs::remove_dir_all(backup_dir)?;
+    restored.sort();
+    Ok(restored)
+}
+
+fn pristine_path(path: &Path) -> PathBuf {
+    data_dir().join(PRISTINE_DIR).join(path)
+}
+
+fn backup_path(path: &Path) -> PathBuf {
+    data_dir().join(BACKUP_DIR).join(path)
+}
+
+fn data_dir() -> PathBuf {
+    env::var_os(DATA_DIR_ENV).map_or_else(|| PathBuf::from(DATA_DIR), PathBuf::from)
+}
@@ -0,0 +1,162 @@
+use crate::exercise::Exercise;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Where the progress of the learner is stored, relative to the rustlings directory
+const PROGRESS_FILE: &str = ".rustlings-state.json";
+// Overrides PROGRESS_FILE, mostly useful for tests
+const PROGRESS_FILE_ENV: &str = "RUSTLINGS_STATE_FILE";
+
+// What is known about a single exercise
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Record {
+    // The hash of the exercise's source when it was last verified
+    pub hash: String,
+    // Whether the exercise passed when it was last verified
+    pub passed: bool,
+    // Unix timestamps of the first and the last verification
+    pub first_attempt: u64,
+    pub last_attempt: u64,
+    // Unix timestamp of the last verification that passed, if any
+    pub last_passed: Option<u64>,
+    // How many times the exercise was verified
+    pub attempts: u32,
+}
+
+// The progress of the learner through all exercises, persisted between runs
+// so that unchanged exercises don't need to be verified again
+#[derive(Deserialize, Serialize, Default, Debug)]
+pub struct ProgressDb {
+    exercises: BTreeMap<String, Record>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl ProgressDb {
+    // Load the progress from disk.
+    // A missing or unreadable file simply means that nothing was verified yet.
+    pub fn load() -> ProgressDb {
+        let path = env::var_os(PROGRESS_FILE_ENV)
+            .map_or_else(|| PathBuf::from(PROGRESS_FILE), PathBuf::from);
+        let mut db = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str::<ProgressDb>(&contents).ok())
+            .unwrap_or_default();
+        db.path = path;
+        db
+    }
+
+    pub fn save(&self) {
+        let serialized = serde_json::to_string_pretty(self).expect("Failed to serialize progress");
+        if let Err(e) = fs::write(&self.path, serialized) {
+            warn!(
+                "Failed to save your progress to {}: {}",
+                self.path.display(),
+                e
+            );
+        }
+    }
+
+    pub fn get(&self, exercise: &Exercise) -> Option<&Record> {
+        self.exercises.get(&exercise.name)
+    }
+
+    // Remember the result of verifying the current source of the exercise
+    pub fn record(&mut self, exercise: &Exercise, passed: bool) {
+        let now = now();
+        let hash = exercise.hash();
+        let record = self
+            .exercises
+            .entry(exercise.name.clone())
+            .or_insert_with(|| Record {
+                hash: String::new(),
+                passed,
+                first_attempt: now,
+                last_attempt: now,
+                last_passed: None,
+                attempts: 0,
+            });
+        record.hash = hash;
+        record.passed = passed;
+        record.last_attempt = now;
+        record.attempts += 1;
+        if passed {
+            record.last_passed = Some(now);
+        }
+    }
+
+    // Whether the exercise passed the last time it was verified,
+    // and wasn't changed since
+    pub fn is_verified(&self, exercise: &Exercise) -> bool {
+        self.get(exercise)
+            .is_some_and(|record| record.passed && record.hash == exercise.hash())
+    }
+
+    // Whether the learner is done with the exercise: the `I AM NOT DONE`
+    // comment was removed and the exercise was verified to pass
+    pub fn is_done(&self, exercise: &Exercise) -> bool {
+        exercise.looks_done() && self.is_verified(exercise)
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn exercise() -> Exercise {
+        Exercise {
+            name: "finished_exercise".into(),
+            path: PathBuf::from("tests/fixture/state/finished_exercise.rs"),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_record() {
+        let exercise = exercise();
+        let mut db = ProgressDb::default();
+        assert!(!db.is_done(&exercise));
+
+        db.record(&exercise, false);
+        assert!(!db.is_done(&exercise));
+        db.record(&exercise, true);
+        assert!(db.is_done(&exercise));
+
+        let record = db.get(&exercise).unwrap();
+        assert_eq!(record.attempts, 2);
+        assert_eq!(record.hash, exercise.hash());
+        assert!(record.last_passed.is_some());
+    }
+
+    #[test]
+    fn test_changed_exercise_is_not_verified() {
+        let exercise = exercise();
+        let mut db = ProgressDb::default();
+        db.record(&exercise, true);
+        db.exercises.get_mut(&exercise.name).unwrap().hash = "0".into();
+        assert!(!db.is_verified(&exercise));
+    }
+
+    #[test]
+    fn test_pending_exercise_is_not_done() {
+        let exercise = Exercise {
+            name: "pending_exercise".into(),
+            path: PathBuf::from("tests/fixture/state/pending_exercise.rs"),
+            ..Default::default()
+        };
+        let mut db = ProgressDb::default();
+        db.record(&exercise, true);
+        assert!(db.is_verified(&exercise));
+        assert!(!db.is_done(&exercise));
+    }
+}
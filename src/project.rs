@@ -1,8 +1,12 @@
+use crate::cargo::VENDOR_DIR;
+use crate::exercise::{Exercise, DEFAULT_EDITION, PROC_MACRO_CRATE};
 use glob::glob;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::env;
 use std::error::Error;
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 /// Contains the structure of resulting rust-project.json file
@@ -11,14 +15,28 @@ use std::process::Command;
 pub struct RustAnalyzerProject {
     sysroot_src: String,
     pub crates: Vec<Crate>,
+    /// The index in `crates` of every vendored crate added so far
+    #[serde(skip)]
+    vendored: HashMap<String, usize>,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct Crate {
     root_module: String,
     edition: String,
-    deps: Vec<String>,
+    deps: Vec<Dependency>,
     cfg: Vec<String>,
+    #[serde(default)]
+    is_proc_macro: bool,
+}
+
+/// A crate another crate depends on, by its index in `crates`
+#[derive(Serialize, Deserialize)]
+pub struct Dependency {
+    #[serde(rename = "crate")]
+    index: usize,
+    /// The name the depending crate uses it by
+    name: String,
 }
 
 impl RustAnalyzerProject {
@@ -26,6 +44,7 @@ impl RustAnalyzerProject {
         RustAnalyzerProject {
             sysroot_src: String::new(),
             crates: Vec::new(),
+            vendored: HashMap::new(),
         }
     }
 
@@ -38,16 +57,29 @@ impl RustAnalyzerProject {
         Ok(())
     }
 
-    /// If path contains .rs extension, add a crate to `rust-project.json`
-    fn path_to_json(&mut self, path: PathBuf) -> Result<(), Box<dyn Error>> {
+    /// If path contains .rs extension, add a crate to `rust-project.json`,
+    /// configured like the exercise it is the crate root of, if any
+    fn path_to_json(&mut self, path: PathBuf, exercise: Option<&Exercise>) -> Result<(), Box<dyn Error>> {
         if let Some(ext) = path.extension() {
             if ext == "rs" {
+                // This allows rust_analyzer to work inside #[test] blocks
+                let mut cfg = vec!["test".to_string()];
+                let mut deps = Vec::new();
+                let mut edition = DEFAULT_EDITION;
+                if let Some(exercise) = exercise {
+                    cfg.extend(exercise.cfg.iter().cloned());
+                    deps = self.dependencies(&exercise.dependencies);
+                    if let Some(root) = exercise.proc_macro_root() {
+                        deps.push(self.proc_macro(root, exercise));
+                    }
+                    edition = exercise.edition();
+                }
                 self.crates.push(Crate {
                     root_module: path.display().to_string(),
-                    edition: "2021".to_string(),
-                    deps: Vec::new(),
-                    // This allows rust_analyzer to work inside #[test] blocks
-                    cfg: vec!["test".to_string()],
+                    edition: edition.to_string(),
+                    deps,
+                    cfg,
+                    is_proc_macro: false,
                 })
             }
         }
@@ -55,12 +87,97 @@ impl RustAnalyzerProject {
         Ok(())
     }
 
+    /// Add the proc-macro crate of a proc_macro exercise with the given
+    /// crate root, and return how the rest of the exercise depends on it
+    fn proc_macro(&mut self, root: PathBuf, exercise: &Exercise) -> Dependency {
+        self.crates.push(Crate {
+            root_module: root.display().to_string(),
+            edition: exercise.edition().to_string(),
+            deps: Vec::new(),
+            cfg: exercise.cfg.clone(),
+            is_proc_macro: true,
+        });
+        Dependency {
+            index: self.crates.len() - 1,
+            name: PROC_MACRO_CRATE.to_string(),
+        }
+    }
+
+    /// Add the vendored crates an exercise depends on, as declared in
+    /// info.toml, and return how the exercise depends on them.
+    /// Crates that aren't vendored are left out.
+    fn dependencies(&mut self, dependencies: &BTreeMap<String, toml::Value>) -> Vec<Dependency> {
+        dependencies
+            .iter()
+            .filter_map(|(name, spec)| {
+                let index = self.vendored_crate(package(name, spec), &features(spec))?;
+                Some(Dependency {
+                    index,
+                    name: name.replace('-', "_"),
+                })
+            })
+            .collect()
+    }
+
+    /// Add the vendored crate of the given package along with its own
+    /// dependencies, unless it was added already, and return its index.
+    /// The given features are enabled, along with the default ones.
+    fn vendored_crate(&mut self, package_name: &str, features: &[String]) -> Option<usize> {
+        if let Some(&index) = self.vendored.get(package_name) {
+            return Some(index);
+        }
+        let dir = vendored_dir(package_name)?;
+        let manifest: toml::Value = toml::from_str(&fs::read_to_string(dir.join("Cargo.toml")).ok()?).ok()?;
+        let lib = manifest.get("lib");
+        let root = lib
+            .and_then(|lib| lib.get("path"))
+            .and_then(toml::Value::as_str)
+            .unwrap_or("src/lib.rs");
+        let edition = manifest
+            .get("package")
+            .and_then(|package| package.get("edition"))
+            .and_then(toml::Value::as_str)
+            .unwrap_or("2015");
+        let mut enabled = features.to_vec();
+        if let Some(default) = manifest.get("features").and_then(|features| features.get("default")) {
+            enabled.push("default".to_string());
+            enabled.extend(strings(default));
+        }
+
+        let index = self.crates.len();
+        self.crates.push(Crate {
+            root_module: dir.join(root).display().to_string(),
+            edition: edition.to_string(),
+            deps: Vec::new(),
+            cfg: enabled.iter().map(|feature| format!("feature=\"{feature}\"")).collect(),
+            is_proc_macro: lib
+                .and_then(|lib| lib.get("proc-macro"))
+                .and_then(toml::Value::as_bool)
+                .unwrap_or(false),
+        });
+        self.vendored.insert(package_name.to_string(), index);
+        if let Some(dependencies) = manifest.get("dependencies").and_then(toml::Value::as_table) {
+            let dependencies: BTreeMap<String, toml::Value> = dependencies.clone().into_iter().collect();
+            self.crates[index].deps = self.dependencies(&dependencies);
+        }
+        Some(index)
+    }
+
     /// Parse the exercises folder for .rs files, any matches will create
     /// a new `crate` in rust-project.json which allows rust-analyzer to
-    /// treat it like a normal binary
-    pub fn exercises_to_json(&mut self) -> Result<(), Box<dyn Error>> {
+    /// treat it like a normal binary.
+    /// The submodules of directory exercises are part of their crate.
+    pub fn exercises_to_json(&mut self, exercises: &[Exercise]) -> Result<(), Box<dyn Error>> {
         for path in glob("./exercises/**/*")? {
-            self.path_to_json(path?)?;
+            let path = path?;
+            let submodule = exercises.iter().any(|exercise| {
+                exercise.path.is_dir() && exercise.contains(&path) && !path.ends_with(exercise.root())
+            });
+            if submodule {
+                continue;
+            }
+            let exercise = exercises.iter().find(|exercise| path.ends_with(exercise.root()));
+            self.path_to_json(path, exercise)?;
         }
         Ok(())
     }
@@ -86,7 +203,7 @@ impl RustAnalyzerProject {
 
         println!("Determined toolchain: {}\n", &toolchain);
 
-        self.sysroot_src = (std::path::Path::new(&*toolchain)
+        self.sysroot_src = (std::path::Path::new(toolchain)
             .join("lib")
             .join("rustlib")
             .join("src")
@@ -97,3 +214,45 @@ impl RustAnalyzerProject {
         Ok(())
     }
 }
+
+/// The package a dependency declared as `name = spec` refers to, which is
+/// `name` unless it is renamed
+fn package<'a>(name: &'a str, spec: &'a toml::Value) -> &'a str {
+    spec.get("package").and_then(toml::Value::as_str).unwrap_or(name)
+}
+
+/// The features a dependency declared as `name = spec` enables
+fn features(spec: &toml::Value) -> Vec<String> {
+    spec.get("features").map(strings).unwrap_or_default()
+}
+
+fn strings(value: &toml::Value) -> Vec<String> {
+    value
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(toml::Value::as_str)
+        .map(str::to_string)
+        .collect()
+}
+
+/// The directory `cargo vendor` put the given package in, which is named
+/// after its version too if several versions are vendored
+fn vendored_dir(package: &str) -> Option<PathBuf> {
+    let dir = Path::new(VENDOR_DIR).join(package);
+    if dir.is_dir() {
+        return Some(dir);
+    }
+    let prefix = format!("{package}-");
+    fs::read_dir(VENDOR_DIR)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix(&prefix))
+                .is_some_and(|version| version.starts_with(|c: char| c.is_ascii_digit()))
+        })
+        .max()
+}
@@ -0,0 +1,76 @@
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// The environment variable passing the seed to the compiled exercise
+pub const SEED_ENV: &str = "RUSTLINGS_SEED";
+// The random inputs, shrinking and checks property tests are written with
+const HARNESS: &str = include_str!("property_harness.rs");
+// The module the harness is compiled into
+const HARNESS_MODULE: &str = "rustlings_property";
+
+// The modules to add to an exercise to run the given property tests.
+// Property tests are written like the body of a test module, and can use
+// everything from the harness.
+// The harness is nested in a module with SEED_ENV, just like it is in this
+// one.
+pub fn modules(properties: &str) -> String {
+    format!(
+        "#[cfg(test)]\nmod {HARNESS_MODULE} {{\n\
+         const SEED_ENV: &str = {SEED_ENV:?};\n\
+         mod harness {{\n{HARNESS}\n}}\n\
+         pub use harness::*;\n}}\n\
+         #[cfg(test)]\nmod properties {{\nuse super::{HARNESS_MODULE}::*;\n{properties}\n}}\n"
+    )
+}
+
+// A seed that differs between runs
+pub fn random_seed() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64);
+    // Mix in the process id, since exercises graded at the same time
+    // shouldn't get the same seed
+    let mut rng = harness::Rng::new(nanos ^ u64::from(process::id()).rotate_left(32));
+    // Keep seeds short enough to type
+    rng.next_u64() % 1_000_000_000
+}
+
+#[path = "property_harness.rs"]
+mod harness;
+
+#[cfg(test)]
+mod test {
+    use super::harness::*;
+
+    #[test]
+    fn test_same_seed_same_inputs() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        assert_eq!(a.vec(20, |rng| rng.i32(-5, 5)), b.vec(20, |rng| rng.i32(-5, 5)));
+    }
+
+    #[test]
+    fn test_shrink_to_minimal_input() {
+        // Fails for any vector containing a number of at least 10
+        let result = std::panic::catch_unwind(|| {
+            check(
+                |rng| rng.vec(50, |rng| rng.i32(0, 1000)),
+                |v: &Vec<i32>| v.iter().all(|&x| x < 10),
+            )
+        });
+        let message = *result.unwrap_err().downcast::<String>().unwrap();
+        assert!(
+            message.starts_with("the property doesn't hold for [10] (shrunk from"),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn test_panics_fail_the_property() {
+        let result = std::panic::catch_unwind(|| {
+            check(|rng| rng.vec(5, |rng| rng.i32(0, 10)), |v: &Vec<i32>| v[0] >= 0)
+        });
+        let message = *result.unwrap_err().downcast::<String>().unwrap();
+        assert!(message.starts_with("the property doesn't hold for [] "), "{message}");
+    }
+}
@@ -0,0 +1,189 @@
+// Property testing for rustlings exercises.
+// This file is compiled into exercises that have property tests, which use
+// it to check the exercise against a reference implementation.
+#![allow(dead_code)]
+
+use std::cell::Cell;
+use std::fmt::Debug;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+
+// How many random inputs every property is checked with
+pub const CASES: usize = 200;
+// How many smaller inputs are tried at most while shrinking a failing one
+const SHRINK_STEPS: usize = 2000;
+
+thread_local! {
+    // Whether panics are expected and shouldn't be printed
+    static QUIET: Cell<bool> = const { Cell::new(false) };
+}
+
+// The seed rustlings runs the exercise with, passed in the environment
+// variable named by SEED_ENV of the module the harness is compiled into
+pub fn seed() -> u64 {
+    std::env::var(super::SEED_ENV)
+        .ok()
+        .and_then(|seed| seed.parse().ok())
+        .unwrap_or(0)
+}
+
+// A small random number generator (SplitMix64), so that every input can be
+// generated again from the seed
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // A number in `low..high`
+    pub fn range(&mut self, low: i64, high: i64) -> i64 {
+        assert!(low < high, "empty range {low}..{high}");
+        low + (self.next_u64() % (high - low) as u64) as i64
+    }
+
+    pub fn i32(&mut self, low: i32, high: i32) -> i32 {
+        self.range(low.into(), high.into()) as i32
+    }
+
+    pub fn bool(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+
+    // A vector of up to `max_len` items
+    pub fn vec<T>(&mut self, max_len: usize, mut item: impl FnMut(&mut Rng) -> T) -> Vec<T> {
+        let len = self.range(0, max_len as i64 + 1) as usize;
+        (0..len).map(|_| item(self)).collect()
+    }
+}
+
+// Values that can be made smaller, to find a minimal failing input
+pub trait Shrink: Clone {
+    // Smaller values to try, the most promising first
+    fn shrink(&self) -> Vec<Self>;
+}
+
+macro_rules! shrink_integer {
+    ($($t:ty),*) => {$(
+        impl Shrink for $t {
+            fn shrink(&self) -> Vec<Self> {
+                let mut smaller = Vec::new();
+                if *self != 0 {
+                    smaller.push(0);
+                }
+                let half = *self / 2;
+                if half != 0 {
+                    smaller.push(half);
+                }
+                let closer = if *self > half { *self - 1 } else { *self + 1 };
+                if closer != half && closer != *self && *self != 0 {
+                    smaller.push(closer);
+                }
+                smaller
+            }
+        }
+    )*};
+}
+
+shrink_integer!(i8, i16, i32, i64, u8, u16, u32, u64, usize);
+
+impl Shrink for bool {
+    fn shrink(&self) -> Vec<Self> {
+        if *self {
+            vec![false]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+impl<T: Shrink> Shrink for Vec<T> {
+    fn shrink(&self) -> Vec<Self> {
+        let mut smaller = Vec::new();
+        // Remove large chunks first, then single items, then shrink items
+        let mut chunk = self.len() / 2;
+        while chunk > 0 {
+            for start in (0..self.len()).step_by(chunk) {
+                let mut without = self.clone();
+                without.drain(start..(start + chunk).min(self.len()));
+                smaller.push(without);
+            }
+            chunk /= 2;
+        }
+        for (i, item) in self.iter().enumerate() {
+            for item in item.shrink() {
+                let mut shrunk = self.clone();
+                shrunk[i] = item;
+                smaller.push(shrunk);
+            }
+        }
+        smaller
+    }
+}
+
+impl<A: Shrink, B: Shrink> Shrink for (A, B) {
+    fn shrink(&self) -> Vec<Self> {
+        let mut smaller: Vec<Self> = self.0.shrink().into_iter().map(|a| (a, self.1.clone())).collect();
+        smaller.extend(self.1.shrink().into_iter().map(|b| (self.0.clone(), b)));
+        smaller
+    }
+}
+
+// Check that `property` holds for inputs made by `generate`.
+// A failing input is shrunk to a minimal one that still fails, which the
+// test then panics with, along with the seed to reproduce it.
+pub fn check<T: Shrink + Debug>(generate: impl Fn(&mut Rng) -> T, property: impl Fn(&T) -> bool) {
+    let seed = seed();
+    let mut rng = Rng::new(seed);
+    for _ in 0..CASES {
+        let input = generate(&mut rng);
+        if holds(&property, &input) {
+            continue;
+        }
+        let minimal = shrink(&property, input.clone());
+        panic!(
+            "the property doesn't hold for {minimal:?} (shrunk from {input:?}, seed {seed})"
+        );
+    }
+}
+
+fn shrink<T: Shrink>(property: &impl Fn(&T) -> bool, mut failing: T) -> T {
+    let mut steps = 0;
+    'shrink: while steps < SHRINK_STEPS {
+        for smaller in failing.shrink() {
+            steps += 1;
+            if !holds(property, &smaller) {
+                failing = smaller;
+                continue 'shrink;
+            }
+        }
+        break;
+    }
+    failing
+}
+
+// Whether the property holds for the input.
+// A panic, like an out of bounds index, means that it doesn't.
+fn holds<T>(property: &impl Fn(&T) -> bool, input: &T) -> bool {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let default = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !QUIET.with(Cell::get) {
+                default(info);
+            }
+        }));
+    });
+    QUIET.with(|quiet| quiet.set(true));
+    let holds = panic::catch_unwind(AssertUnwindSafe(|| property(input))).unwrap_or(false);
+    QUIET.with(|quiet| quiet.set(false));
+    holds
+}
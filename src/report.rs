@@ -0,0 +1,453 @@
+use crate::diagnostics;
+use crate::diff::Changes;
+use crate::exercise::{normalize_output, Exercise, ExerciseOutput, Grade, Outcome};
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+// Why exercises skipped by `rustlings verify` weren't graded
+const SKIPPED_MESSAGE: &str = "unchanged since it last passed";
+
+// The file formats a grading run can be reported in
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReportFormat {
+    Junit,
+    Tap,
+}
+
+// A report requested on the command line as `<format>=<path>`
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReportSpec {
+    pub format: ReportFormat,
+    pub path: PathBuf,
+}
+
+impl FromStr for ReportSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (format, path) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected `junit=<path>` or `tap=<path>`, got `{s}`"))?;
+        let format = match format {
+            "junit" => ReportFormat::Junit,
+            "tap" => ReportFormat::Tap,
+            _ => {
+                return Err(format!(
+                    "unknown report format `{format}`, expected `junit` or `tap`"
+                ))
+            }
+        };
+        if path.is_empty() {
+            return Err(format!("missing path in `{s}`"));
+        }
+        Ok(ReportSpec {
+            format,
+            path: PathBuf::from(path),
+        })
+    }
+}
+
+// The result of grading one exercise, as shown in a report
+struct Case {
+    // The position of the exercise in info.toml
+    index: usize,
+    name: String,
+    path: PathBuf,
+    // The directory the exercise lives in, like `variables`
+    topic: String,
+    duration: Duration,
+    // A short description of the failure and the output explaining it
+    failure: Option<(String, String)>,
+    stdout: String,
+    // Why the exercise wasn't graded, like being unchanged since it last
+    // passed
+    skipped: Option<String>,
+}
+
+// Collects grading results so they can be written as JUnit XML or TAP
+#[derive(Default)]
+pub struct Report {
+    cases: Vec<Case>,
+}
+
+impl Report {
+    pub fn new() -> Report {
+        Report::default()
+    }
+
+    // Record the grade of the exercise at the given position in info.toml
+    pub fn add(&mut self, index: usize, exercise: &Exercise, grade: &Grade) {
+        let topic = topic(exercise);
+        let mut skipped = None;
+        let (failure, stdout) = match &grade.outcome {
+            Outcome::Passed(output) => (None, output.stdout.clone()),
+            Outcome::Skipped(reason) => {
+                skipped = Some(reason.clone());
+                (None, String::new())
+            }
+            Outcome::CompileFailed(output) => {
+                let details = format!(
+                    "{}{}{}",
+                    output.stdout,
+                    diagnostics::render(&output.diagnostics),
+                    output.stderr
+                );
+                (
+                    Some(("Compilation failed".to_string(), details)),
+                    String::new(),
+                )
+            }
+            Outcome::RunFailed(output) => {
+                let details = format!("{}{}", output.stdout, output.stderr);
+                (Some(("Running failed".to_string(), details)), String::new())
+            }
+            Outcome::LimitExceeded(limit, output) => {
+                let details = format!("{}{}", output.stdout, output.stderr);
+                (
+                    Some((format!("Stopped: it {limit}"), details)),
+                    String::new(),
+                )
+            }
+            Outcome::TestsModified(regions) => {
+                let details = format!("Modified: {}", regions.join(", "));
+                (Some(("Tests modified".to_string(), details)), String::new())
+            }
+            Outcome::WrongOutput(output, expected) => {
+                let details = output_diff(output, expected).unified;
+                (Some(("Wrong output".to_string(), details)), String::new())
+            }
+            Outcome::UnexpectedlyCompiled => (
+                Some((
+                    "Compiled unexpectedly".to_string(),
+                    format!("Expected errors: {}", exercise.expect_error.join(", ")),
+                )),
+                String::new(),
+            ),
+            Outcome::WrongErrors(output, found) => {
+                let details = format!(
+                    "Expected errors: {}\nFound errors: {}\n{}",
+                    exercise.expect_error.join(", "),
+                    found.join(", "),
+                    diagnostics::render(&output.diagnostics)
+                );
+                (Some(("Wrong errors".to_string(), details)), String::new())
+            }
+            Outcome::Unformatted(_, diff) => (
+                Some(("Not formatted like rustfmt would".to_string(), diff.clone())),
+                String::new(),
+            ),
+            Outcome::TooSlow(benchmark, _) => (
+                Some((format!("Too slow: {}", benchmark.growth()), benchmark.to_string())),
+                String::new(),
+            ),
+        };
+        self.cases.push(Case {
+            index,
+            name: exercise.name.clone(),
+            path: exercise.path.clone(),
+            topic,
+            duration: grade.duration(),
+            failure: failure.map(|(message, details)| (message, strip_ansi(&details))),
+            stdout: strip_ansi(&stdout),
+            skipped,
+        });
+    }
+
+    // Record that the exercise at the given position in info.toml was
+    // skipped since it is unchanged since it last passed
+    pub fn add_skipped(&mut self, index: usize, exercise: &Exercise) {
+        self.cases.push(Case {
+            index,
+            name: exercise.name.clone(),
+            path: exercise.path.clone(),
+            topic: topic(exercise),
+            duration: Duration::ZERO,
+            failure: None,
+            stdout: String::new(),
+            skipped: Some(SKIPPED_MESSAGE.to_string()),
+        });
+    }
+
+    // Write the report in the requested format
+    pub fn write(&mut self, spec: &ReportSpec) -> io::Result<()> {
+        self.cases.sort_by_key(|case| case.index);
+        let contents = match spec.format {
+            ReportFormat::Junit => self.junit(),
+            ReportFormat::Tap => self.tap(),
+        };
+        if let Some(parent) = spec.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&spec.path, contents)
+    }
+
+    // Write every requested report, warning about those that failed
+    pub fn write_all(&mut self, specs: &[ReportSpec]) {
+        for spec in specs {
+            if let Err(e) = self.write(spec) {
+                warn!("Failed to write the report {}: {}", spec.path.display(), e);
+            }
+        }
+    }
+
+    // Group cases by topic, keeping topics in the order they appear
+    fn topics(&self) -> Vec<(&str, Vec<&Case>)> {
+        let mut topics: Vec<(&str, Vec<&Case>)> = Vec::new();
+        for case in &self.cases {
+            match topics.iter_mut().find(|(topic, _)| *topic == case.topic) {
+                Some((_, cases)) => cases.push(case),
+                None => topics.push((&case.topic, vec![case])),
+            }
+        }
+        topics
+    }
+
+    fn junit(&self) -> String {
+        let failures = self.cases.iter().filter(|c| c.failure.is_some()).count();
+        let skipped = self.cases.iter().filter(|c| c.skipped.is_some()).count();
+        let time: Duration = self.cases.iter().map(|c| c.duration).sum();
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            xml,
+            r#"<testsuites name="rustlings" tests="{}" failures="{failures}" skipped="{skipped}" time="{:.3}">"#,
+            self.cases.len(),
+            time.as_secs_f64()
+        );
+        for (topic, cases) in self.topics() {
+            let failures = cases.iter().filter(|c| c.failure.is_some()).count();
+            let skipped = cases.iter().filter(|c| c.skipped.is_some()).count();
+            let time: Duration = cases.iter().map(|c| c.duration).sum();
+            let _ = writeln!(
+                xml,
+                r#"  <testsuite name="{}" tests="{}" failures="{failures}" skipped="{skipped}" time="{:.3}">"#,
+                escape_xml(topic),
+                cases.len(),
+                time.as_secs_f64()
+            );
+            for case in cases {
+                let _ = write!(
+                    xml,
+                    r#"    <testcase name="{}" classname="{}" file="{}" time="{:.3}""#,
+                    escape_xml(&case.name),
+                    escape_xml(topic),
+                    escape_xml(&case.path.display().to_string()),
+                    case.duration.as_secs_f64()
+                );
+                if case.failure.is_none() && case.stdout.is_empty() && case.skipped.is_none() {
+                    xml.push_str("/>\n");
+                    continue;
+                }
+                xml.push_str(">\n");
+                if let Some(reason) = &case.skipped {
+                    let _ = writeln!(xml, r#"      <skipped message="{}"/>"#, escape_xml(reason));
+                }
+                if let Some((message, details)) = &case.failure {
+                    let _ = writeln!(
+                        xml,
+                        r#"      <failure message="{}">{}</failure>"#,
+                        escape_xml(message),
+                        escape_xml(details)
+                    );
+                }
+                if !case.stdout.is_empty() {
+                    let _ = writeln!(
+                        xml,
+                        "      <system-out>{}</system-out>",
+                        escape_xml(&case.stdout)
+                    );
+                }
+                xml.push_str("    </testcase>\n");
+            }
+            xml.push_str("  </testsuite>\n");
+        }
+        xml.push_str("</testsuites>\n");
+        xml
+    }
+
+    fn tap(&self) -> String {
+        let mut tap = format!("TAP version 13\n1..{}\n", self.cases.len());
+        let mut number = 0;
+        for (topic, cases) in self.topics() {
+            let _ = writeln!(tap, "# {topic}");
+            for case in cases {
+                number += 1;
+                let status = if case.failure.is_some() {
+                    "not ok"
+                } else {
+                    "ok"
+                };
+                let directive = match &case.skipped {
+                    Some(reason) => format!(" # SKIP {reason}"),
+                    None => String::new(),
+                };
+                let _ = writeln!(tap, "{status} {number} - {topic}/{}{directive}", case.name);
+                let _ = writeln!(tap, "  ---");
+                let _ = writeln!(tap, "  duration_ms: {}", case.duration.as_millis());
+                if let Some((message, details)) = &case.failure {
+                    let _ = writeln!(tap, "  message: {message:?}");
+                    let _ = writeln!(tap, "  output: |");
+                    for line in details.lines() {
+                        let _ = writeln!(tap, "    {line}");
+                    }
+                }
+                let _ = writeln!(tap, "  ...");
+            }
+        }
+        tap
+    }
+}
+
+// How the output of an exercise differs from the expected output
+pub fn output_diff(output: &ExerciseOutput, expected: &str) -> Changes {
+    let actual = normalize_output(&output.stdout);
+    Changes::between(expected, &actual, "expected output", "actual output")
+}
+
+// The directory the exercise lives in. Exercises outside of a topic
+// directory, like the quizzes, are grouped together.
+fn topic(exercise: &Exercise) -> String {
+    exercise
+        .path
+        .parent()
+        .and_then(Path::file_name)
+        .map_or_else(
+            || "exercises".to_string(),
+            |topic| topic.to_string_lossy().to_string(),
+        )
+}
+
+fn strip_ansi(text: &str) -> String {
+    console::strip_ansi_codes(text).to_string()
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters other than whitespace are not allowed in XML
+            c if c.is_control() && !matches!(c, '\n' | '\r' | '\t') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::exercise::ExerciseOutput;
+
+    fn output(stdout: &str) -> ExerciseOutput {
+        ExerciseOutput {
+            stdout: stdout.to_string(),
+            stderr: String::new(),
+            limit: None,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    fn report() -> Report {
+        let mut report = Report::new();
+        let passing = Exercise {
+            name: "variables1".into(),
+            path: PathBuf::from("exercises/variables/variables1.rs"),
+            ..Default::default()
+        };
+        let failing = Exercise {
+            name: "move_semantics1".into(),
+            path: PathBuf::from("exercises/move_semantics/move_semantics1.rs"),
+            ..Default::default()
+        };
+        report.add(
+            1,
+            &failing,
+            &Grade {
+                outcome: Outcome::RunFailed(output("assertion `left == right` failed <&>")),
+                compile_time: Duration::from_millis(100),
+                run_time: Some(Duration::from_millis(20)),
+                tests: None,
+                seed: None,
+                bench: None,
+            },
+        );
+        report.add(
+            0,
+            &passing,
+            &Grade {
+                outcome: Outcome::Passed(output("")),
+                compile_time: Duration::from_millis(50),
+                run_time: Some(Duration::from_millis(5)),
+                tests: None,
+                seed: None,
+                bench: None,
+            },
+        );
+        report.cases.sort_by_key(|case| case.index);
+        report
+    }
+
+    #[test]
+    fn test_parse_report_spec() {
+        assert_eq!(
+            "junit=out/report.xml".parse(),
+            Ok(ReportSpec {
+                format: ReportFormat::Junit,
+                path: PathBuf::from("out/report.xml"),
+            })
+        );
+        assert!("xml=report.xml".parse::<ReportSpec>().is_err());
+        assert!("tap".parse::<ReportSpec>().is_err());
+    }
+
+    #[test]
+    fn test_junit() {
+        let xml = report().junit();
+        assert!(xml.contains(
+            r#"<testsuites name="rustlings" tests="2" failures="1" skipped="0" time="0.175">"#
+        ));
+        assert!(xml.contains(
+            r#"<testsuite name="variables" tests="1" failures="0" skipped="0" time="0.055">"#
+        ));
+        assert!(xml.contains(r#"<testcase name="variables1" classname="variables" file="exercises/variables/variables1.rs" time="0.055"/>"#));
+        assert!(xml.contains(
+            r#"<failure message="Running failed">assertion `left == right` failed &lt;&amp;&gt;</failure>"#
+        ));
+    }
+
+    #[test]
+    fn test_tap() {
+        let tap = report().tap();
+        assert!(tap.starts_with("TAP version 13\n1..2\n# variables\nok 1 - variables/variables1\n"));
+        assert!(tap.contains("not ok 2 - move_semantics/move_semantics1\n"));
+        assert!(tap.contains("  message: \"Running failed\"\n  output: |\n    assertion"));
+    }
+
+    #[test]
+    fn test_skipped() {
+        let mut report = report();
+        let skipped = Exercise {
+            name: "quiz1".into(),
+            path: PathBuf::from("exercises/quiz1.rs"),
+            ..Default::default()
+        };
+        report.add_skipped(2, &skipped);
+        let xml = report.junit();
+        assert!(xml.contains(r#"tests="3" failures="1" skipped="1""#));
+        assert!(xml.contains(
+            r#"<testsuite name="exercises" tests="1" failures="0" skipped="1" time="0.000">"#
+        ));
+        assert!(xml.contains(r#"<skipped message="unchanged since it last passed"/>"#));
+        assert!(report
+            .tap()
+            .contains("ok 3 - exercises/quiz1 # SKIP unchanged since it last passed\n"));
+    }
+}
@@ -1,73 +1,105 @@
-use std::process::Command;
-
-use crate::exercise::{Exercise, Mode};
-use crate::verify::test;
-use indicatif::ProgressBar;
+use crate::events::{emit_grade, emit_started};
+use crate::exercise::{Exercise, GradeOptions, Mode, Outcome};
+use crate::pristine;
+use crate::ui::json_output;
+use crate::verify::{report_failure, spinner, test};
 
 // Invoke the rust compiler on the path of the given exercise,
 // and run the ensuing binary.
 // The verbose argument helps determine whether or not to show
 // the output from the test harnesses (if the mode of the exercise is test)
-pub fn run(exercise: &Exercise, verbose: bool) -> Result<(), ()> {
+// Property tests run with the given seed, or a random one.
+pub fn run(exercise: &Exercise, verbose: bool, seed: Option<u64>) -> Result<(), ()> {
+    if json_output() {
+        emit_started(exercise);
+        let grade = exercise.grade_with(&GradeOptions {
+            seed,
+            ..Default::default()
+        });
+        return match emit_grade(exercise, &grade) {
+            true => Ok(()),
+            false => Err(()),
+        };
+    }
     match exercise.mode {
-        Mode::Test => test(exercise, verbose)?,
-        Mode::Compile => compile_and_run(exercise)?,
-        Mode::Clippy => compile_and_run(exercise)?,
-        Mode::BuildScript => test(exercise, verbose)?,
+        Mode::Test => test(exercise, verbose, seed)?,
+        Mode::Compile => compile_and_run(exercise, seed)?,
+        Mode::Clippy => compile_and_run(exercise, seed)?,
+        Mode::BuildScript => test(exercise, verbose, seed)?,
+        Mode::Miri => test(exercise, verbose, seed)?,
+        Mode::CompileFail => test(exercise, verbose, seed)?,
+        Mode::Doctest => test(exercise, verbose, seed)?,
+        Mode::Fmt => test(exercise, verbose, seed)?,
+        Mode::Cargo => test(exercise, verbose, seed)?,
+        Mode::ProcMacro => test(exercise, verbose, seed)?,
+        Mode::Custom => test(exercise, verbose, seed)?,
     }
     Ok(())
 }
 
-// Resets the exercise by stashing the changes.
-pub fn reset(exercise: &Exercise) -> Result<(), ()> {
-    let command = Command::new("git")
-        .args(["stash", "--"])
-        .arg(&exercise.path)
-        .spawn();
+// Resets the given exercises to their original versions.
+// The versions they replace are backed up for `undo_reset`.
+pub fn reset(exercises: &[&Exercise]) -> Result<(), ()> {
+    for exercise in exercises {
+        for file in exercise.files() {
+            if pristine::added(exercise, &file) {
+                continue;
+            }
+            if let Err(e) = pristine::original(&file) {
+                warn!("The original version of {} is not available: {}", file.display(), e);
+                return Err(());
+            }
+        }
+    }
+    let reset = pristine::restore(exercises).map_err(|e| {
+        warn!("Failed to reset {} exercise(s): {}", exercises.len(), e);
+    })?;
 
-    match command {
-        Ok(_) => Ok(()),
-        Err(_) => Err(()),
+    if reset.is_empty() {
+        println!("Nothing to reset, the exercise(s) are unchanged.");
+        return Ok(());
+    }
+    for exercise in &reset {
+        success!("Reset {}", exercise);
     }
+    println!("Run `rustlings reset --undo` to get your previous version back.");
+    Ok(())
 }
 
-// Invoke the rust compiler on the path of the given exercise
-// and run the ensuing binary.
-// This is strictly for non-test binaries, so output is displayed
-fn compile_and_run(exercise: &Exercise) -> Result<(), ()> {
-    let progress_bar = ProgressBar::new_spinner();
-    progress_bar.set_message(format!("Compiling {exercise}..."));
-    progress_bar.enable_steady_tick(100);
-
-    let compilation_result = exercise.compile();
-    let compilation = match compilation_result {
-        Ok(compilation) => compilation,
-        Err(output) => {
-            progress_bar.finish_and_clear();
-            warn!(
-                "Compilation of {} failed!, Compiler error message:\n",
-                exercise
-            );
-            println!("{}", output.stderr);
-            return Err(());
-        }
-    };
+// Puts back the versions replaced by the last reset
+pub fn undo_reset() -> Result<(), ()> {
+    let restored = pristine::undo().map_err(|e| {
+        warn!("Failed to undo the last reset: {}", e);
+    })?;
+    if restored.is_empty() {
+        warn!("There is no reset to undo");
+        return Err(());
+    }
+    for path in restored {
+        success!("Restored {}", path.display());
+    }
+    Ok(())
+}
 
-    progress_bar.set_message(format!("Running {exercise}..."));
-    let result = compilation.run();
+// Grade the given exercise like `verify` does, and show what the ensuing
+// binary printed.
+// This is strictly for non-test binaries, so output is displayed
+fn compile_and_run(exercise: &Exercise, seed: Option<u64>) -> Result<(), ()> {
+    let progress_bar = spinner(exercise);
+    let grade = exercise.grade_with(&GradeOptions {
+        seed,
+        ..Default::default()
+    });
     progress_bar.finish_and_clear();
 
-    match result {
-        Ok(output) => {
+    match grade.outcome {
+        Outcome::Passed(output) => {
             println!("{}", output.stdout);
             success!("Successfully ran {}", exercise);
             Ok(())
         }
-        Err(output) => {
-            println!("{}", output.stdout);
-            println!("{}", output.stderr);
-
-            warn!("Ran {} with errors", exercise);
+        failure => {
+            report_failure(exercise, &failure);
             Err(())
         }
     }
@@ -0,0 +1,119 @@
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::channel;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// The number of jobs used when none was given on the command line
+pub fn default_jobs() -> usize {
+    thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+// Run `job` for every item on a pool of at most `jobs` worker threads.
+// Since compiling and running exercises blocks on rustc and cargo, every
+// worker is a plain OS thread. `on_finished` is called on the calling thread
+// as soon as a job is done, together with the index of its item and how long
+// it took. Returning `ControlFlow::Break` from it stops the pool from starting
+// any more jobs; jobs that are already running are still waited for, and
+// `on_finished` is still called with their results.
+pub fn schedule<T, R>(
+    items: &[T],
+    jobs: usize,
+    job: impl Fn(&T) -> R + Sync,
+    mut on_finished: impl FnMut(usize, R, Duration) -> ControlFlow<()>,
+) where
+    T: Sync,
+    R: Send,
+{
+    let next = AtomicUsize::new(0);
+    let cancelled = AtomicBool::new(false);
+    let (tx, rx) = channel();
+
+    thread::scope(|scope| {
+        for _ in 0..jobs.max(1).min(items.len()) {
+            let tx = tx.clone();
+            let (next, cancelled, job) = (&next, &cancelled, &job);
+            scope.spawn(move || loop {
+                if cancelled.load(Ordering::SeqCst) {
+                    break;
+                }
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                let Some(item) = items.get(index) else {
+                    break;
+                };
+                let start = Instant::now();
+                let result = job(item);
+                if tx.send((index, result, start.elapsed())).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(tx);
+
+        for (index, result, elapsed) in rx.iter() {
+            if on_finished(index, result, elapsed).is_break() {
+                cancelled.store(true, Ordering::SeqCst);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_schedule_respects_jobs() {
+        let running = AtomicUsize::new(0);
+        let max_running = Mutex::new(0);
+        let items: Vec<usize> = (0..16).collect();
+        let mut finished = Vec::new();
+        schedule(
+            &items,
+            3,
+            |item| {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                {
+                    let mut max_running = max_running.lock().unwrap();
+                    *max_running = (*max_running).max(now);
+                }
+                thread::sleep(Duration::from_millis(10));
+                running.fetch_sub(1, Ordering::SeqCst);
+                item * 2
+            },
+            |index, result, _| {
+                assert_eq!(result, index * 2);
+                finished.push(index);
+                ControlFlow::Continue(())
+            },
+        );
+        finished.sort();
+        assert_eq!(finished, items);
+        assert!(*max_running.lock().unwrap() <= 3);
+    }
+
+    #[test]
+    fn test_schedule_stops_on_break() {
+        let items: Vec<usize> = (0..100).collect();
+        let started = AtomicUsize::new(0);
+        let mut finished = 0;
+        schedule(
+            &items,
+            2,
+            |_| {
+                started.fetch_add(1, Ordering::SeqCst);
+                thread::sleep(Duration::from_millis(5));
+            },
+            |_, _, _| {
+                finished += 1;
+                ControlFlow::Break(())
+            },
+        );
+        // Every job that was started still finished
+        assert_eq!(finished, started.load(Ordering::SeqCst));
+        assert!(finished < items.len());
+    }
+}
@@ -0,0 +1,230 @@
+use crate::exercise::{Exercise, Mode};
+use crate::pristine;
+use std::fs;
+
+// Comments marking a region of an exercise the learner must not change
+pub const LOCKED_BEGIN: &str = "// BEGIN LOCKED";
+pub const LOCKED_END: &str = "// END LOCKED";
+
+// A part of an exercise the learner isn't supposed to change
+#[derive(Debug, PartialEq)]
+struct Region {
+    // How the region is shown to the learner, like `mod tests`
+    name: String,
+    text: String,
+}
+
+// The protected regions of the exercise which differ from its original
+// version: the test module of test exercises and the locked regions.
+// Whether a file without an original version was modified is unknown, so
+// it isn't reported.
+// The regions of a directory exercise are named along with their file.
+pub fn modified_regions(exercise: &Exercise) -> Vec<String> {
+    let mut modified = Vec::new();
+    for file in exercise.files() {
+        let Ok(current) = fs::read(&file) else {
+            continue;
+        };
+        let Ok(original) = pristine::original(&file) else {
+            continue;
+        };
+        let regions = compare(
+            &String::from_utf8_lossy(&original),
+            &String::from_utf8_lossy(&current),
+            matches!(exercise.mode, Mode::Test),
+        );
+        match file.strip_prefix(&exercise.path) {
+            Ok(name) if exercise.path.is_dir() => modified.extend(
+                regions
+                    .into_iter()
+                    .map(|region| format!("{region} in {}", name.display())),
+            ),
+            _ => modified.extend(regions),
+        }
+    }
+    modified
+}
+
+// The names of the protected regions of `original` which are missing or
+// different in `current`
+fn compare(original: &str, current: &str, test_modules: bool) -> Vec<String> {
+    let current = protected_regions(current, test_modules);
+    protected_regions(original, test_modules)
+        .into_iter()
+        .filter(|region| {
+            !current
+                .iter()
+                .any(|r| r.name == region.name && normalize(&r.text) == normalize(&region.text))
+        })
+        .map(|region| region.name)
+        .collect()
+}
+
+fn protected_regions(source: &str, test_modules: bool) -> Vec<Region> {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut regions = Vec::new();
+    let mut locked = 0;
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].trim();
+        if line == LOCKED_BEGIN {
+            locked += 1;
+            let end = lines[i..]
+                .iter()
+                .position(|line| line.trim() == LOCKED_END)
+                .map_or(lines.len(), |end| i + end);
+            regions.push(Region {
+                name: format!("locked region {locked}"),
+                text: lines[i + 1..end].join("\n"),
+            });
+            i = end;
+        } else if test_modules && line == "#[cfg(test)]" {
+            let rest = lines[i + 1..].join("\n");
+            if let Some((name, text)) = module(&rest) {
+                let end = i + 1 + text.lines().count();
+                regions.push(Region { name, text });
+                i = end;
+            }
+        }
+        i += 1;
+    }
+    regions
+}
+
+// The name and the text of the module at the start of the given source,
+// up to its closing brace
+fn module(source: &str) -> Option<(String, String)> {
+    let declaration = source.trim_start();
+    let name = declaration
+        .strip_prefix("pub ")
+        .unwrap_or(declaration)
+        .strip_prefix("mod ")?
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .next()?;
+    let start = source.len() - declaration.len();
+
+    let mut depth = 0;
+    let mut chars = declaration.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    let text = &source[..start + index + 1];
+                    return Some((format!("mod {name}"), text.to_string()));
+                }
+            }
+            // Braces in strings and comments don't count
+            '"' => {
+                while let Some((_, c)) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '/' if chars.peek().map(|&(_, c)| c) == Some('/') => {
+                for (_, c) in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '\'' if declaration[index..].starts_with("'{'")
+                || declaration[index..].starts_with("'}'") =>
+            {
+                chars.nth(1);
+            }
+            _ => {}
+        }
+    }
+    // An unclosed module extends to the end of the file
+    Some((format!("mod {name}"), source.to_string()))
+}
+
+// Ignore changes to whitespace, like reformatting the code with rustfmt,
+// and to the trailing commas rustfmt adds or removes when it splits or joins
+// lines
+fn normalize(text: &str) -> String {
+    let mut normalized = String::new();
+    let mut chars = text.chars().filter(|c| !c.is_whitespace()).peekable();
+    while let Some(c) = chars.next() {
+        if c == ',' && matches!(chars.peek(), Some(')' | ']' | '}')) {
+            continue;
+        }
+        normalized.push(c);
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXERCISE: &str = r#"// I AM NOT DONE
+fn double(x: i32) -> i32 {
+    x * 2
+}
+
+// BEGIN LOCKED
+const FACTOR: i32 = 2;
+// END LOCKED
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn doubles() {
+        assert_eq!(double(2), 4, "{}", '}');
+    }
+}
+"#;
+
+    #[test]
+    fn test_protected_regions() {
+        let regions = protected_regions(EXERCISE, true);
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].name, "locked region 1");
+        assert_eq!(regions[0].text, "const FACTOR: i32 = 2;");
+        assert_eq!(regions[1].name, "mod tests");
+        assert!(regions[1].text.starts_with("mod tests {"));
+        assert!(regions[1].text.ends_with("    }\n}"));
+    }
+
+    #[test]
+    fn test_solving_the_exercise_is_not_tampering() {
+        let solved = EXERCISE
+            .replace("// I AM NOT DONE\n", "")
+            .replace("x * 2", "x + x")
+            .replace("    assert_eq!", "        assert_eq!");
+        assert!(compare(EXERCISE, &solved, true).is_empty());
+    }
+
+    #[test]
+    fn test_reformatting_is_not_tampering() {
+        let reformatted = EXERCISE
+            .replace("    #[test]\n    fn doubles() {", "\n    #[test] fn doubles()\n    {")
+            .replace(
+                r#"assert_eq!(double(2), 4, "{}", '}');"#,
+                "assert_eq!(\n            double(2),\n            4,\n            \"{}\",\n            '}',\n        );",
+            );
+        assert!(compare(EXERCISE, &reformatted, true).is_empty());
+    }
+
+    #[test]
+    fn test_modified_tests() {
+        let weakened = EXERCISE.replace("assert_eq!(double(2), 4", "assert_eq!(4, 4");
+        assert_eq!(compare(EXERCISE, &weakened, true), ["mod tests"]);
+
+        let removed = &EXERCISE[..EXERCISE.find("#[cfg(test)]").unwrap()];
+        assert_eq!(compare(EXERCISE, removed, true), ["mod tests"]);
+
+        let unlocked = EXERCISE.replace("FACTOR: i32 = 2", "FACTOR: i32 = 3");
+        assert_eq!(compare(EXERCISE, &unlocked, true), ["locked region 1"]);
+    }
+}
@@ -1,33 +1,92 @@
 macro_rules! warn {
-    ($fmt:literal, $ex:expr) => {{
+    ($fmt:literal) => {
+        warn!("{}", $fmt)
+    };
+    ($fmt:literal, $($ex:expr),+) => {{
         use console::{style, Emoji};
         use std::env;
-        let formatstr = format!($fmt, $ex);
-        if env::var("NO_EMOJI").is_ok() {
-            println!("{} {}", style("!").red(), style(formatstr).red());
-        } else {
-            println!(
-                "{} {}",
-                style(Emoji("⚠️ ", "!")).red(),
-                style(formatstr).red()
-            );
+        let formatstr = format!($fmt, $($ex),+);
+        // With JSON output, the events carry the same information
+        if !$crate::ui::json_output() {
+            if env::var("NO_EMOJI").is_ok() {
+                println!("{} {}", style("!").red(), style(formatstr).red());
+            } else {
+                println!(
+                    "{} {}",
+                    style(Emoji("⚠️ ", "!")).red(),
+                    style(formatstr).red()
+                );
+            }
         }
     }};
 }
 
 macro_rules! success {
-    ($fmt:literal, $ex:expr) => {{
+    ($fmt:literal) => {
+        success!("{}", $fmt)
+    };
+    ($fmt:literal, $($ex:expr),+) => {{
         use console::{style, Emoji};
         use std::env;
-        let formatstr = format!($fmt, $ex);
-        if env::var("NO_EMOJI").is_ok() {
-            println!("{} {}", style("✓").green(), style(formatstr).green());
-        } else {
-            println!(
-                "{} {}",
-                style(Emoji("✅", "✓")).green(),
-                style(formatstr).green()
-            );
+        let formatstr = format!($fmt, $($ex),+);
+        // With JSON output, the events carry the same information
+        if !$crate::ui::json_output() {
+            if env::var("NO_EMOJI").is_ok() {
+                println!("{} {}", style("✓").green(), style(formatstr).green());
+            } else {
+                println!(
+                    "{} {}",
+                    style(Emoji("✅", "✓")).green(),
+                    style(formatstr).green()
+                );
+            }
         }
     }};
 }
+
+// Print a line for humans, which goes to stderr with JSON output so that
+// stdout only has events
+macro_rules! note {
+    ($($arg:tt)*) => {
+        if $crate::ui::json_output() {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
+}
+
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static JSON_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+// The format in which commands report their results
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    // Colored text, progress bars and emoji for humans
+    Human,
+    // One JSON event per line for scripts
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(Format::Human),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("unknown format `{s}`, expected `human` or `json`")),
+        }
+    }
+}
+
+pub fn set_format(format: Format) {
+    JSON_OUTPUT.store(format == Format::Json, Ordering::SeqCst);
+}
+
+// Whether human readable output should be replaced by JSON events
+pub fn json_output() -> bool {
+    JSON_OUTPUT.load(Ordering::SeqCst)
+}
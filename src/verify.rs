@@ -1,21 +1,45 @@
-use crate::exercise::{CompiledExercise, Exercise, Mode, State};
+use crate::cargo::CargoCommand;
+use crate::diagnostics;
+use crate::diff;
+use crate::events::{self, Event};
+use crate::exercise::{Exercise, ExerciseOutput, Grade, GradeOptions, Mode, Outcome, State};
+use crate::progress::ProgressDb;
+use crate::report::{output_diff, Report, ReportSpec};
+use crate::scheduler::schedule;
+use crate::ui::json_output;
 use console::style;
 use indicatif::{ProgressBar, ProgressStyle};
+use std::collections::HashMap;
 use std::env;
+use std::ops::ControlFlow;
 
 // Verify that the provided container of Exercise objects
 // can be compiled and run without any failures.
 // Any such failures will be reported to the end user.
 // If the Exercise being verified is a test, the verbose boolean
 // determines whether or not the test harness outputs are displayed.
+// Up to `jobs` exercises are compiled and run ahead at the same time,
+// but they are still reported one by one in the given order.
+// Exercises that are done and unchanged since they were verified to pass
+// are skipped, and the results of the others are recorded in `db`.
+// Every exercise graded, even ahead of a failing one, is also written to the
+// requested reports.
 pub fn verify<'a>(
     exercises: impl IntoIterator<Item = &'a Exercise>,
     progress: (usize, usize),
     verbose: bool,
     success_hints: bool,
+    jobs: usize,
+    reports: &[ReportSpec],
+    db: &mut ProgressDb,
 ) -> Result<(), &'a Exercise> {
+    let exercises: Vec<&Exercise> = exercises.into_iter().collect();
     let (num_done, total) = progress;
-    let bar = ProgressBar::new(total as u64);
+    let bar = if json_output() {
+        ProgressBar::hidden()
+    } else {
+        ProgressBar::new(total as u64)
+    };
     let mut percentage = num_done as f32 / total as f32 * 100.0;
     bar.set_style(ProgressStyle::default_bar()
         .template("Progress: [{bar:60.green/red}] {pos}/{len} {msg}")
@@ -24,22 +48,82 @@ pub fn verify<'a>(
     bar.set_position(num_done as u64);
     bar.set_message(format!("({:.1} %)", percentage));
 
-    for exercise in exercises {
-        let compile_result = match exercise.mode {
-            Mode::Test => compile_and_test(exercise, RunMode::Interactive, verbose, success_hints),
-            Mode::Compile => compile_and_run_interactively(exercise, success_hints),
-            Mode::Clippy => compile_only(exercise, success_hints),
-            Mode::BuildScript => compile_and_test(exercise, RunMode::Interactive, verbose, success_hints),
+    let skipped: Vec<bool> = exercises.iter().map(|exercise| db.is_done(exercise)).collect();
+    let pending: Vec<usize> = (0..exercises.len()).filter(|&i| !skipped[i]).collect();
 
-        };
-        if !compile_result.unwrap_or(false) {
-            return Err(exercise);
+    let mut results = Report::new();
+    let mut finished = HashMap::new();
+    let mut next = 0;
+    let mut failed = None;
+    let mut progress_bar = None;
+
+    // Report the exercises that were skipped or graded so far, in order,
+    // up to the first one that failed
+    let mut advance = |finished: &mut HashMap<usize, Grade>| {
+        if failed.is_some() {
+            return ControlFlow::Break(());
         }
-        percentage += 100.0 / total as f32;
-        bar.inc(1);
-        bar.set_message(format!("({:.1} %)", percentage));
+        while let Some(&exercise) = exercises.get(next) {
+            if skipped[next] {
+                results.add_skipped(next, exercise);
+                if json_output() {
+                    events::emit(&Event::Skipped {
+                        exercise: &exercise.name,
+                        mode: exercise.mode,
+                        reason: None,
+                    });
+                }
+            } else {
+                let Some(grade) = finished.remove(&next) else {
+                    progress_bar.get_or_insert_with(|| spinner(exercise));
+                    break;
+                };
+                if let Some(progress_bar) = progress_bar.take() {
+                    progress_bar.finish_and_clear();
+                }
+                results.add(next, exercise, &grade);
+                db.record(exercise, grade.outcome.passed());
+                db.save();
+                // Exercises that can't be graded here don't hold up the others
+                let skipped = matches!(grade.outcome, Outcome::Skipped(_));
+                let done = if json_output() {
+                    events::emit_grade(exercise, &grade) && exercise.looks_done() || skipped
+                } else {
+                    report(exercise, grade, RunMode::Interactive, verbose, success_hints)
+                };
+                if !done {
+                    failed = Some(exercise);
+                    return ControlFlow::Break(());
+                }
+            }
+            percentage += 100.0 / total as f32;
+            bar.inc(1);
+            bar.set_message(format!("({:.1} %)", percentage));
+            next += 1;
+        }
+        ControlFlow::Continue(())
+    };
+
+    let _ = advance(&mut finished);
+    let grade = |&index: &usize| {
+        if json_output() {
+            events::emit_started(exercises[index]);
+        }
+        exercises[index].grade()
+    };
+    schedule(&pending, jobs, grade, |index, grade, _| {
+        finished.insert(pending[index], grade);
+        advance(&mut finished)
+    });
+    for (index, grade) in finished {
+        results.add(index, exercises[index], &grade);
+    }
+    results.write_all(reports);
+
+    match failed {
+        Some(exercise) => Err(exercise),
+        None => Ok(()),
     }
-    Ok(())
 }
 
 enum RunMode {
@@ -48,101 +132,225 @@ enum RunMode {
 }
 
 // Compile and run the resulting test harness of the given Exercise
-pub fn test(exercise: &Exercise, verbose: bool) -> Result<(), ()> {
-    compile_and_test(exercise, RunMode::NonInteractive, verbose, false)?;
-    Ok(())
-}
-
-// Invoke the rust compiler without running the resulting binary
-fn compile_only(exercise: &Exercise, success_hints: bool) -> Result<bool, ()> {
-    let progress_bar = ProgressBar::new_spinner();
-    progress_bar.set_message(format!("Compiling {exercise}..."));
-    progress_bar.enable_steady_tick(100);
-
-    let _ = compile(exercise, &progress_bar)?;
+// Property tests run with the given seed, or a random one.
+pub fn test(exercise: &Exercise, verbose: bool, seed: Option<u64>) -> Result<(), ()> {
+    let progress_bar = spinner(exercise);
+    let grade = exercise.grade_with(&GradeOptions {
+        seed,
+        ..Default::default()
+    });
     progress_bar.finish_and_clear();
 
-    Ok(prompt_for_completion(exercise, None, success_hints))
+    if report(exercise, grade, RunMode::NonInteractive, verbose, false) {
+        Ok(())
+    } else {
+        Err(())
+    }
 }
 
-// Compile the given Exercise and run the resulting binary in an interactive mode
-fn compile_and_run_interactively(exercise: &Exercise, success_hints: bool) -> Result<bool, ()> {
+pub fn spinner(exercise: &Exercise) -> ProgressBar {
+    if json_output() {
+        return ProgressBar::hidden();
+    }
     let progress_bar = ProgressBar::new_spinner();
-    progress_bar.set_message(format!("Compiling {exercise}..."));
-    progress_bar.enable_steady_tick(100);
-
-    let compilation = compile(exercise, &progress_bar)?;
-
-    progress_bar.set_message(format!("Running {exercise}..."));
-    let result = compilation.run();
-    progress_bar.finish_and_clear();
-
-    let output = match result {
-        Ok(output) => output,
-        Err(output) => {
-            warn!("Ran {} with errors", exercise);
-            println!("{}", output.stdout);
-            println!("{}", output.stderr);
-            return Err(());
+    let message = match exercise.mode {
+        Mode::Test | Mode::BuildScript | Mode::Miri | Mode::Doctest | Mode::ProcMacro => {
+            format!("Testing {exercise}...")
         }
+        Mode::Compile | Mode::Clippy | Mode::CompileFail | Mode::Fmt => {
+            format!("Compiling {exercise}...")
+        }
+        Mode::Cargo => format!("Building {exercise} with cargo..."),
+        Mode::Custom => format!("Checking {exercise}..."),
     };
-
-    Ok(prompt_for_completion(exercise, Some(output.stdout), success_hints))
-}
-
-// Compile the given Exercise as a test harness and display
-// the output if verbose is set to true
-fn compile_and_test(exercise: &Exercise, run_mode: RunMode, verbose: bool, success_hints: bool) -> Result<bool, ()> {
-    let progress_bar = ProgressBar::new_spinner();
-    progress_bar.set_message(format!("Testing {exercise}..."));
+    progress_bar.set_message(message);
     progress_bar.enable_steady_tick(100);
+    progress_bar
+}
 
-    let compilation = compile(exercise, &progress_bar)?;
-    let result = compilation.run();
-    progress_bar.finish_and_clear();
+// Show the outcome of grading the given Exercise. In interactive mode,
+// successful exercises prompt for completion.
+// Returns whether the exercise may be considered done.
+fn report(
+    exercise: &Exercise,
+    grade: Grade,
+    run_mode: RunMode,
+    verbose: bool,
+    success_hints: bool,
+) -> bool {
+    let output = match grade.outcome {
+        // Only holds up a single run, which can't be considered done
+        skipped @ Outcome::Skipped(_) => {
+            report_failure(exercise, &skipped);
+            return matches!(run_mode, RunMode::Interactive);
+        }
+        Outcome::Passed(output) => {
+            if let Some(benchmark) = &grade.bench {
+                println!("Timings of {exercise}, {}:", benchmark.growth());
+                print!("{benchmark}");
+            }
+            output
+        }
+        failure => {
+            report_failure(exercise, &failure);
+            // Failing property tests fail the run
+            if let (Outcome::RunFailed(_), Some(seed)) = (&failure, grade.seed) {
+                report_seed(exercise, seed);
+            }
+            return false;
+        }
+    };
 
-    match result {
-        Ok(output) => {
+    match exercise.mode {
+        Mode::Test | Mode::BuildScript | Mode::Miri | Mode::Doctest | Mode::ProcMacro | Mode::Custom => {
             if verbose {
                 println!("{}", output.stdout);
             }
-            if let RunMode::Interactive = run_mode {
-                Ok(prompt_for_completion(exercise, None, success_hints))
-            } else {
-                Ok(true)
+            if let RunMode::NonInteractive = run_mode {
+                return true;
             }
+            prompt_for_completion(exercise, None, success_hints)
         }
-        Err(output) => {
-            warn!(
-                "Testing of {} failed! Please try again. Here's the output:",
-                exercise
-            );
-            println!("{}", output.stdout);
-            Err(())
+        Mode::Compile => prompt_for_completion(exercise, Some(output.stdout), success_hints),
+        Mode::Clippy | Mode::Fmt => prompt_for_completion(exercise, None, success_hints),
+        Mode::CompileFail => {
+            let errors = diagnostics::render(&output.diagnostics);
+            if let RunMode::NonInteractive = run_mode {
+                print!("{errors}");
+                success!("{} failed to compile as expected!", exercise);
+                return true;
+            }
+            prompt_for_completion(exercise, Some(errors), success_hints)
+        }
+        Mode::Cargo => {
+            // What the program printed, if cargo ran it
+            let stdout = match exercise.cargo_project().command {
+                CargoCommand::Run | CargoCommand::Clippy => Some(output.stdout),
+                CargoCommand::Test | CargoCommand::Build => None,
+            };
+            if let RunMode::NonInteractive = run_mode {
+                if let Some(stdout) = stdout {
+                    println!("{stdout}");
+                }
+                return true;
+            }
+            prompt_for_completion(exercise, stdout, success_hints)
         }
     }
 }
 
-// Compile the given Exercise and return an object with information
-// about the state of the compilation
-fn compile<'a, 'b>(
-    exercise: &'a Exercise,
-    progress_bar: &'b ProgressBar,
-) -> Result<CompiledExercise<'a>, ()> {
-    let compilation_result = exercise.compile();
-
-    match compilation_result {
-        Ok(compilation) => Ok(compilation),
-        Err(output) => {
-            progress_bar.finish_and_clear();
+// Print why grading the given Exercise failed
+pub fn report_failure(exercise: &Exercise, outcome: &Outcome) {
+    match outcome {
+        Outcome::Passed(_) => {}
+        Outcome::CompileFailed(output) => {
             warn!(
                 "Compiling of {} failed! Please try again. Here's the output:",
                 exercise
             );
+            print_compile_output(output);
+        }
+        Outcome::RunFailed(output) => match exercise.mode {
+            Mode::Test | Mode::BuildScript | Mode::Miri | Mode::Doctest | Mode::ProcMacro | Mode::Custom => {
+                warn!(
+                    "Testing of {} failed! Please try again. Here's the output:",
+                    exercise
+                );
+                println!("{}", output.stdout);
+                // Where uncaptured panics and allocation errors end up
+                if !output.stderr.trim().is_empty() {
+                    println!("{}", output.stderr);
+                }
+            }
+            Mode::Compile | Mode::Clippy | Mode::CompileFail | Mode::Fmt | Mode::Cargo => {
+                warn!("Ran {} with errors", exercise);
+                println!("{}", output.stdout);
+                println!("{}", output.stderr);
+            }
+        },
+        Outcome::LimitExceeded(limit, output) => {
+            warn!("Running {} was stopped: it {}!", exercise, limit);
+            println!("{}", output.stdout);
             println!("{}", output.stderr);
-            Err(())
         }
+        Outcome::TestsModified(regions) => {
+            warn!(
+                "The tests of {} were modified ({})! Please restore them, for example with `rustlings reset {}`.",
+                exercise,
+                regions.join(", "),
+                exercise.name
+            );
+        }
+        Outcome::TooSlow(benchmark, _) => {
+            warn!(
+                "The tests of {} pass, but it is too slow: {}! Here are the timings:",
+                exercise,
+                benchmark.growth()
+            );
+            print!("{benchmark}");
+        }
+        Outcome::Skipped(reason) => warn!("Skipped {}: {}.", exercise, reason),
+        Outcome::UnexpectedlyCompiled => warn!(
+            "{} compiled, but it must fail to compile with {}!",
+            exercise,
+            error_codes(&exercise.expect_error)
+        ),
+        Outcome::Unformatted(_, diff) => {
+            warn!(
+                "{} isn't formatted the way rustfmt formats it! Please run `rustfmt --edition {} {}` or fix it by hand. Here's the diff:",
+                exercise,
+                exercise.edition(),
+                exercise
+            );
+            print!("{}", diff::colored(diff));
+        }
+        Outcome::WrongErrors(output, found) => {
+            warn!(
+                "{} failed to compile with {}, but it must fail with {}! Here's the output:",
+                exercise,
+                error_codes(found),
+                error_codes(&exercise.expect_error)
+            );
+            print_compile_output(output);
+        }
+        Outcome::WrongOutput(output, expected) => {
+            warn!(
+                "Ran {}, but it didn't print the expected output! Here's how they differ:",
+                exercise
+            );
+            print!("{}", output_diff(output, expected).colored());
+            if !output.stderr.trim().is_empty() {
+                println!("{}", output.stderr);
+            }
+        }
+    }
+}
+
+// A list of error codes for messages about compile_fail exercises
+fn error_codes(codes: &[String]) -> String {
+    match codes {
+        [] => "no error codes".to_string(),
+        codes => codes.join(", "),
+    }
+}
+
+// Print how to run the property tests of the given Exercise again
+// with the same inputs
+pub fn report_seed(exercise: &Exercise, seed: u64) {
+    println!(
+        "The property tests ran with seed {seed}. To check the same inputs again, run `rustlings run {} --seed {seed}`.",
+        exercise.name
+    );
+}
+
+// Print the diagnostics of a failed compilation along with anything
+// else the compiler wrote
+pub fn print_compile_output(output: &ExerciseOutput) {
+    if !output.stdout.trim().is_empty() {
+        println!("{}", output.stdout);
     }
+    print!("{}", diagnostics::render(&output.diagnostics));
+    println!("{}", output.stderr);
 }
 
 fn prompt_for_completion(exercise: &Exercise, prompt_output: Option<String>, success_hints: bool) -> bool {
@@ -155,6 +363,13 @@ fn prompt_for_completion(exercise: &Exercise, prompt_output: Option<String>, suc
         Mode::Test => success!("Successfully tested {}!", exercise),
         Mode::Clippy => success!("Successfully compiled {}!", exercise),
         Mode::BuildScript => success!("Successfully compiled {}!", exercise),
+        Mode::Miri => success!("Successfully tested {} with Miri!", exercise),
+        Mode::CompileFail => success!("{} failed to compile as expected!", exercise),
+        Mode::Doctest => success!("Successfully ran the doc-tests of {}!", exercise),
+        Mode::Fmt => success!("Successfully formatted {}!", exercise),
+        Mode::Cargo => success!("Successfully built {} with cargo!", exercise),
+        Mode::ProcMacro => success!("Successfully tested {} and its macros!", exercise),
+        Mode::Custom => success!("Successfully checked {}!", exercise),
     }
 
     let no_emoji = env::var("NO_EMOJI").is_ok();
@@ -170,6 +385,13 @@ fn prompt_for_completion(exercise: &Exercise, prompt_output: Option<String>, suc
         Mode::Test => "The code is compiling, and the tests pass!",
         Mode::Clippy => clippy_success_msg,
         Mode::BuildScript => "Build script works!",
+        Mode::Miri => "The tests pass, and Miri found no undefined behavior!",
+        Mode::CompileFail => "The code fails to compile with the expected errors!",
+        Mode::Doctest => "The code is compiling, and the doc-tests pass!",
+        Mode::Fmt => "The code is compiling, and it is formatted like rustfmt would!",
+        Mode::Cargo => "The package builds, and cargo is happy!",
+        Mode::ProcMacro => "The macros expand, and the tests pass!",
+        Mode::Custom => "The checks pass!",
     };
     println!();
     if no_emoji {
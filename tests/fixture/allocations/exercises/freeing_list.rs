@@ -0,0 +1,54 @@
+use std::ptr::NonNull;
+
+struct Node {
+    val: i32,
+    next: Option<NonNull<Node>>,
+}
+
+struct List {
+    start: Option<NonNull<Node>>,
+}
+
+impl List {
+    fn new() -> List {
+        List { start: None }
+    }
+
+    fn push(&mut self, val: i32) {
+        let node = Box::new(Node { val, next: self.start });
+        self.start = NonNull::new(Box::into_raw(node));
+    }
+
+    fn sum(&self) -> i32 {
+        let mut sum = 0;
+        let mut node = self.start;
+        while let Some(ptr) = node {
+            let node_ref = unsafe { ptr.as_ref() };
+            sum += node_ref.val;
+            node = node_ref.next;
+        }
+        sum
+    }
+}
+
+impl Drop for List {
+    fn drop(&mut self) {
+        let mut node = self.start;
+        while let Some(ptr) = node {
+            let boxed = unsafe { Box::from_raw(ptr.as_ptr()) };
+            node = boxed.next;
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sums() {
+        let mut list = List::new();
+        list.push(1);
+        list.push(2);
+        assert_eq!(list.sum(), 3);
+    }
+}
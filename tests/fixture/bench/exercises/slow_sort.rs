@@ -0,0 +1,22 @@
+// Sorts correctly, but in quadratic time
+fn sort(array: &mut [i64]) {
+    for i in 0..array.len() {
+        for j in 0..array.len() - i - 1 {
+            if array[j] > array[j + 1] {
+                array.swap(j, j + 1);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorts() {
+        let mut array = [3, 1, 2];
+        sort(&mut array);
+        assert_eq!(array, [1, 2, 3]);
+    }
+}
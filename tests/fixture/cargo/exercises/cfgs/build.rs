@@ -0,0 +1,4 @@
+fn main() {
+    println!("cargo::rustc-check-cfg=cfg(configured)");
+    println!("cargo::rustc-cfg=configured");
+}
@@ -0,0 +1,9 @@
+fn main() {}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn build_script_ran() {
+        assert!(cfg!(configured));
+    }
+}
@@ -0,0 +1,7 @@
+fn main() {
+    if cfg!(feature = "loud") {
+        println!("HELLO!");
+    } else {
+        println!("hello");
+    }
+}
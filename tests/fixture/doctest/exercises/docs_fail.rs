@@ -0,0 +1,17 @@
+/// Doubles the given number.
+///
+/// ```
+/// assert_eq!(docs_fail::double(2), 4);
+/// ```
+pub fn double(x: i32) -> i32 {
+    x * 3
+}
+
+/// Triples the given number.
+///
+/// ```
+/// assert_eq!(docs_fail::triple(2), 6);
+/// ```
+pub fn triple(x: i32) -> i32 {
+    x * 3
+}
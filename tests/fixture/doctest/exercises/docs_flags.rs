@@ -0,0 +1,7 @@
+/// Runs only when debug assertions are disabled.
+///
+/// ```
+/// assert!(!cfg!(debug_assertions));
+/// docs_flags::release();
+/// ```
+pub fn release() {}
@@ -0,0 +1,8 @@
+/// Doubles the given number.
+///
+/// ```
+/// assert_eq!(docs_pass::double(2), 4);
+/// ```
+pub fn double(x: i32) -> i32 {
+    x * 2
+}
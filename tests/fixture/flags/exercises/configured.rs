@@ -0,0 +1,7 @@
+#[allow(unexpected_cfgs)]
+fn main() {
+    #[cfg(shout)]
+    println!("HELLO!");
+    #[cfg(not(shout))]
+    println!("hello");
+}
@@ -0,0 +1,7 @@
+fn unused() {}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn passes() {}
+}
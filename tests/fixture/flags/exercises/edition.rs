@@ -0,0 +1,9 @@
+// Let chains need the 2024 edition
+fn main() {
+    let word = Some("Chained!");
+    if let Some(word) = word
+        && word.ends_with('!')
+    {
+        println!("{word}");
+    }
+}
@@ -0,0 +1,8 @@
+// infiniteLoop.rs
+// This exercise never finishes, so it has to be stopped by its timeout.
+
+fn main() {
+    loop {
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+}
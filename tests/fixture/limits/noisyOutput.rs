@@ -0,0 +1,8 @@
+// noisyOutput.rs
+// This exercise writes output forever, so it has to be stopped by the output limit.
+
+fn main() {
+    loop {
+        println!("Are we there yet?");
+    }
+}
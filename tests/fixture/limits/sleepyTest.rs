@@ -0,0 +1,13 @@
+// sleepyTest.rs
+// The test binary cargo runs outlives the timeout, so it has to be stopped
+// along with cargo.
+
+fn main() {}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn sleeps() {
+        std::thread::sleep(std::time::Duration::from_secs(15));
+    }
+}
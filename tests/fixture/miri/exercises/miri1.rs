@@ -0,0 +1,19 @@
+// Reads a value after freeing it, which only Miri notices
+fn read_after_free() -> i32 {
+    let boxed = Box::new(42);
+    let ptr = Box::into_raw(boxed);
+    unsafe {
+        drop(Box::from_raw(ptr));
+        *ptr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads() {
+        let _ = read_after_free();
+    }
+}
@@ -0,0 +1,10 @@
+// I AM NOT DONE
+
+#[derive(Debug)]
+pub struct Asparagus;
+
+impl Asparagus {
+    pub(crate) fn new() -> Asparagus {
+        Asparagus
+    }
+}
@@ -0,0 +1,8 @@
+mod garden;
+
+use crate::garden::vegetables::Asparagus;
+
+fn main() {
+    let plant = Asparagus::new();
+    println!("I'm growing {plant:?}!");
+}
@@ -0,0 +1,11 @@
+mod area;
+
+#[cfg(test)]
+mod tests {
+    use crate::area::square;
+
+    #[test]
+    fn squares() {
+        assert_eq!(square(3), 9);
+    }
+}
@@ -0,0 +1,6 @@
+// Counts to 4 instead of 3
+fn main() {
+    for i in 1..=4 {
+        println!("{i}");
+    }
+}
@@ -0,0 +1,5 @@
+fn main() {
+    // Trailing whitespace and blank lines don't matter
+    println!("Hello world!   ");
+    println!();
+}
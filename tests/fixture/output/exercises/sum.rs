@@ -0,0 +1,10 @@
+use std::io::{self, BufRead};
+
+fn main() {
+    let sum: i32 = io::stdin()
+        .lock()
+        .lines()
+        .map(|line| line.unwrap().trim().parse::<i32>().unwrap())
+        .sum();
+    println!("{sum}");
+}
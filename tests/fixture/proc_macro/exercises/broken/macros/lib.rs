@@ -0,0 +1,30 @@
+// The `Describe` derive macro of broken.
+//
+// A derive macro is a function from the tokens of the item it is derived for
+// to the tokens it adds after that item. Only the `proc_macro` crate of the
+// standard library is used, so the tokens are taken apart and put together by
+// hand.
+
+use proc_macro::{TokenStream, TokenTree};
+
+#[proc_macro_derive(Describe)]
+pub fn derive_describe(input: TokenStream) -> TokenStream {
+    let name = type_name(input);
+    // TODO: Implement the `name` function of the trait, which returns `name`.
+    format!("impl Describe for {name} {{ }}").parse().unwrap()
+}
+
+// The name of the struct or enum in `input`, which comes right after the
+// `struct` or `enum` keyword.
+fn type_name(input: TokenStream) -> String {
+    let mut tokens = input.into_iter();
+    while let Some(token) = tokens.next() {
+        if let TokenTree::Ident(ident) = token {
+            let keyword = ident.to_string();
+            if keyword == "struct" || keyword == "enum" {
+                return tokens.next().unwrap().to_string();
+            }
+        }
+    }
+    panic!("Describe can only be derived for structs and enums");
+}
@@ -0,0 +1,46 @@
+// describe.rs
+//
+// Procedural macros are Rust functions that run at compile time: they take
+// the tokens of some code and return new tokens to compile instead. They live
+// in a crate of their own, which is the `macros` directory next to this file.
+// Its `Describe` derive macro should implement the `Describe` trait below, but
+// it doesn't generate any code yet. Make it generate the implementation!
+//
+// Execute `rustlings hint describe` or use the `hint` watch subcommand for
+// a hint.
+
+use macros::Describe;
+
+trait Describe {
+    // The name of the type
+    fn name() -> &'static str;
+}
+
+#[derive(Describe)]
+struct Pancake;
+
+#[allow(dead_code)]
+#[derive(Describe)]
+enum Topping {
+    Syrup,
+    Butter,
+}
+
+fn main() {
+    println!("{} with {}", Pancake::name(), Topping::name());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_structs() {
+        assert_eq!(Pancake::name(), "Pancake");
+    }
+
+    #[test]
+    fn describes_enums() {
+        assert_eq!(Topping::name(), "Topping");
+    }
+}
@@ -0,0 +1,61 @@
+// log_calls.rs
+//
+// Attribute macros replace the item they are put on. The `log_calls` attribute
+// macro in the `macros` directory should make a function call `log_call` with
+// its name before doing anything else, but for now it leaves the function
+// unchanged. Make it log the calls!
+//
+// Execute `rustlings hint log_calls` or use the `hint` watch subcommand for
+// a hint.
+
+use macros::log_calls;
+use std::cell::RefCell;
+
+thread_local! {
+    static CALLS: RefCell<Vec<&'static str>> = RefCell::new(Vec::new());
+}
+
+// Record a call of the function with the given name
+fn log_call(name: &'static str) {
+    CALLS.with(|calls| calls.borrow_mut().push(name));
+}
+
+// The names of the functions called so far, in order
+fn calls() -> Vec<&'static str> {
+    CALLS.with(|calls| calls.borrow().clone())
+}
+
+#[log_calls]
+fn mix(flour: u32, milk: u32) -> u32 {
+    flour + milk
+}
+
+#[log_calls]
+fn bake(batter: u32) -> u32 {
+    if batter == 0 {
+        return 0;
+    }
+    batter / 2
+}
+
+fn main() {
+    let pancakes = bake(mix(200, 300));
+    println!("Made {pancakes} pancakes with {:?}", calls());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn logs_calls_in_order() {
+        assert_eq!(bake(mix(2, 4)), 3);
+        assert_eq!(calls(), vec!["mix", "bake"]);
+    }
+
+    #[test]
+    fn logs_early_returns() {
+        assert_eq!(bake(0), 0);
+        assert_eq!(calls(), vec!["bake"]);
+    }
+}
@@ -0,0 +1,18 @@
+// Only sorts the first two elements
+fn sort(array: &mut [i32]) {
+    if array.len() >= 2 && array[0] > array[1] {
+        array.swap(0, 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorts_two_elements() {
+        let mut array = [2, 1];
+        sort(&mut array);
+        assert_eq!(array, [1, 2]);
+    }
+}
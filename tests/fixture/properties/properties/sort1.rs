@@ -0,0 +1,15 @@
+use super::*;
+
+#[test]
+fn sorts_like_the_standard_library() {
+    check(
+        |rng| rng.vec(20, |rng| rng.i32(0, 100)),
+        |input: &Vec<i32>| {
+            let mut sorted = input.clone();
+            sort(&mut sorted);
+            let mut expected = input.clone();
+            expected.sort();
+            sorted == expected
+        },
+    );
+}
@@ -0,0 +1,6 @@
+// I AM NOT DONE
+
+fn main() {
+    let x = 5;
+    println!("x has the value {}", x);
+}
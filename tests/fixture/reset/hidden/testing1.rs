@@ -0,0 +1,11 @@
+use super::*;
+
+#[test]
+fn doubles_negative() {
+    assert_eq!(double(-3), -6);
+}
+
+#[test]
+fn doubles_large() {
+    assert_eq!(double(1000), 2000);
+}
@@ -0,0 +1,7 @@
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn greets() {
+        assert_eq!(greeting::greet("Ferris"), "Hello, Ferris!");
+    }
+}
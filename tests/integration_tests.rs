@@ -3,7 +3,51 @@ use glob::glob;
 use predicates::boolean::PredicateBooleanExt;
 use std::fs::File;
 use std::io::Read;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// A state file no other test uses, so that tests don't see each other's progress
+fn state_file() -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    std::env::temp_dir().join(format!(
+        "rustlings_state_{}_{}.json",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::SeqCst)
+    ))
+}
+
+// A directory for the data rustlings keeps, like the original versions of
+// the exercises, so that tests don't leave it behind in the fixtures
+fn data_dir() -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!(
+        "rustlings_data_{}_{}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::SeqCst)
+    ))
+}
+
+// A temporary directory no other test uses
+fn temp_dir(name: &str) -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    std::env::temp_dir().join(format!(
+        "rustlings_{name}_{}_{}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::SeqCst)
+    ))
+}
+
+fn rustlings_with_state(state: &Path) -> Command {
+    let mut cmd = Command::cargo_bin("rustlings").unwrap();
+    cmd.env("RUSTLINGS_STATE_FILE", state)
+        .env("RUSTLINGS_DATA_DIR", data_dir());
+    cmd
+}
+
+fn rustlings() -> Command {
+    rustlings_with_state(&state_file())
+}
 
 #[test]
 fn runs_without_arguments() {
@@ -13,8 +57,7 @@ fn runs_without_arguments() {
 
 #[test]
 fn fails_when_in_wrong_dir() {
-    Command::cargo_bin("rustlings")
-        .unwrap()
+    rustlings()
         .current_dir("tests/")
         .assert()
         .code(1);
@@ -22,8 +65,7 @@ fn fails_when_in_wrong_dir() {
 
 #[test]
 fn verify_all_success() {
-    Command::cargo_bin("rustlings")
-        .unwrap()
+    rustlings()
         .arg("verify")
         .current_dir("tests/fixture/success")
         .assert()
@@ -32,19 +74,35 @@ fn verify_all_success() {
 
 #[test]
 fn verify_fails_if_some_fails() {
-    Command::cargo_bin("rustlings")
-        .unwrap()
+    rustlings()
         .arg("verify")
         .current_dir("tests/fixture/failure")
         .assert()
         .code(1);
 }
 
+#[test]
+fn verify_all_success_with_jobs() {
+    rustlings()
+        .args(["verify", "--jobs", "4"])
+        .current_dir("tests/fixture/success")
+        .assert()
+        .success();
+}
+
+#[test]
+fn verify_fails_if_some_fails_with_jobs() {
+    rustlings()
+        .args(["verify", "--jobs", "4"])
+        .current_dir("tests/fixture/failure")
+        .assert()
+        .code(1);
+}
+
 #[test]
 fn run_single_compile_success() {
-    Command::cargo_bin("rustlings")
-        .unwrap()
-        .args(&["run", "compSuccess"])
+    rustlings()
+        .args(["run", "compSuccess"])
         .current_dir("tests/fixture/success/")
         .assert()
         .success();
@@ -52,19 +110,27 @@ fn run_single_compile_success() {
 
 #[test]
 fn run_single_compile_failure() {
-    Command::cargo_bin("rustlings")
-        .unwrap()
-        .args(&["run", "compFailure"])
+    rustlings()
+        .args(["run", "compFailure"])
         .current_dir("tests/fixture/failure/")
         .assert()
         .code(1);
 }
 
+#[test]
+fn run_single_compile_failure_counts_errors() {
+    rustlings()
+        .args(["run", "compFailure"])
+        .current_dir("tests/fixture/failure/")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("1 error, 0 warnings"));
+}
+
 #[test]
 fn run_single_test_success() {
-    Command::cargo_bin("rustlings")
-        .unwrap()
-        .args(&["run", "testSuccess"])
+    rustlings()
+        .args(["run", "testSuccess"])
         .current_dir("tests/fixture/success/")
         .assert()
         .success();
@@ -72,9 +138,8 @@ fn run_single_test_success() {
 
 #[test]
 fn run_single_test_failure() {
-    Command::cargo_bin("rustlings")
-        .unwrap()
-        .args(&["run", "testFailure"])
+    rustlings()
+        .args(["run", "testFailure"])
         .current_dir("tests/fixture/failure/")
         .assert()
         .code(1);
@@ -82,9 +147,8 @@ fn run_single_test_failure() {
 
 #[test]
 fn run_single_test_not_passed() {
-    Command::cargo_bin("rustlings")
-        .unwrap()
-        .args(&["run", "testNotPassed.rs"])
+    rustlings()
+        .args(["run", "testNotPassed.rs"])
         .current_dir("tests/fixture/failure/")
         .assert()
         .code(1);
@@ -92,8 +156,7 @@ fn run_single_test_not_passed() {
 
 #[test]
 fn run_single_test_no_filename() {
-    Command::cargo_bin("rustlings")
-        .unwrap()
+    rustlings()
         .arg("run")
         .current_dir("tests/fixture/")
         .assert()
@@ -102,9 +165,8 @@ fn run_single_test_no_filename() {
 
 #[test]
 fn run_single_test_no_exercise() {
-    Command::cargo_bin("rustlings")
-        .unwrap()
-        .args(&["run", "compNoExercise.rs"])
+    rustlings()
+        .args(["run", "compNoExercise.rs"])
         .current_dir("tests/fixture/failure")
         .assert()
         .code(1);
@@ -112,30 +174,27 @@ fn run_single_test_no_exercise() {
 
 #[test]
 fn reset_single_exercise() {
-    Command::cargo_bin("rustlings")
-        .unwrap()
-        .args(&["reset", "intro1"])
+    rustlings()
+        .args(["reset", "intro1"])
         .assert()
         .code(0);
 }
 
 #[test]
 fn reset_no_exercise() {
-    Command::cargo_bin("rustlings")
-        .unwrap()
+    rustlings()
         .arg("reset")
         .assert()
         .code(1)
         .stderr(predicates::str::contains(
-            "positional arguments not provided",
+            "Name an exercise or a topic to reset",
         ));
 }
 
 #[test]
 fn get_hint_for_single_test() {
-    Command::cargo_bin("rustlings")
-        .unwrap()
-        .args(&["hint", "testFailure"])
+    rustlings()
+        .args(["hint", "testFailure"])
         .current_dir("tests/fixture/failure")
         .assert()
         .code(0)
@@ -169,9 +228,8 @@ fn all_exercises_require_confirmation() {
 
 #[test]
 fn run_compile_exercise_does_not_prompt() {
-    Command::cargo_bin("rustlings")
-        .unwrap()
-        .args(&["run", "pending_exercise"])
+    rustlings()
+        .args(["run", "pending_exercise"])
         .current_dir("tests/fixture/state")
         .assert()
         .code(0)
@@ -180,9 +238,8 @@ fn run_compile_exercise_does_not_prompt() {
 
 #[test]
 fn run_test_exercise_does_not_prompt() {
-    Command::cargo_bin("rustlings")
-        .unwrap()
-        .args(&["run", "pending_test_exercise"])
+    rustlings()
+        .args(["run", "pending_test_exercise"])
         .current_dir("tests/fixture/state")
         .assert()
         .code(0)
@@ -191,9 +248,8 @@ fn run_test_exercise_does_not_prompt() {
 
 #[test]
 fn run_single_test_success_with_output() {
-    Command::cargo_bin("rustlings")
-        .unwrap()
-        .args(&["--nocapture", "run", "testSuccess"])
+    rustlings()
+        .args(["--nocapture", "run", "testSuccess"])
         .current_dir("tests/fixture/success/")
         .assert()
         .code(0)
@@ -202,9 +258,8 @@ fn run_single_test_success_with_output() {
 
 #[test]
 fn run_single_test_success_without_output() {
-    Command::cargo_bin("rustlings")
-        .unwrap()
-        .args(&["run", "testSuccess"])
+    rustlings()
+        .args(["run", "testSuccess"])
         .current_dir("tests/fixture/success/")
         .assert()
         .code(0)
@@ -213,9 +268,8 @@ fn run_single_test_success_without_output() {
 
 #[test]
 fn run_rustlings_list() {
-    Command::cargo_bin("rustlings")
-        .unwrap()
-        .args(&["list"])
+    rustlings()
+        .args(["list"])
         .current_dir("tests/fixture/success")
         .assert()
         .success();
@@ -223,9 +277,14 @@ fn run_rustlings_list() {
 
 #[test]
 fn run_rustlings_list_no_pending() {
-    Command::cargo_bin("rustlings")
-        .unwrap()
-        .args(&["list"])
+    let state = state_file();
+    rustlings_with_state(&state)
+        .arg("verify")
+        .current_dir("tests/fixture/success")
+        .assert()
+        .success();
+    rustlings_with_state(&state)
+        .args(["list"])
         .current_dir("tests/fixture/success")
         .assert()
         .success()
@@ -234,9 +293,14 @@ fn run_rustlings_list_no_pending() {
 
 #[test]
 fn run_rustlings_list_both_done_and_pending() {
-    Command::cargo_bin("rustlings")
-        .unwrap()
-        .args(&["list"])
+    let state = state_file();
+    rustlings_with_state(&state)
+        .args(["run", "finished_exercise"])
+        .current_dir("tests/fixture/state")
+        .assert()
+        .success();
+    rustlings_with_state(&state)
+        .args(["list"])
         .current_dir("tests/fixture/state")
         .assert()
         .success()
@@ -245,9 +309,8 @@ fn run_rustlings_list_both_done_and_pending() {
 
 #[test]
 fn run_rustlings_list_without_pending() {
-    Command::cargo_bin("rustlings")
-        .unwrap()
-        .args(&["list", "--solved"])
+    rustlings()
+        .args(["list", "--solved"])
         .current_dir("tests/fixture/state")
         .assert()
         .success()
@@ -256,11 +319,1098 @@ fn run_rustlings_list_without_pending() {
 
 #[test]
 fn run_rustlings_list_without_done() {
-    Command::cargo_bin("rustlings")
-        .unwrap()
-        .args(&["list", "--unsolved"])
+    rustlings()
+        .args(["list", "--unsolved"])
         .current_dir("tests/fixture/state")
         .assert()
         .success()
         .stdout(predicates::str::contains("Done").not());
 }
+
+#[test]
+fn run_single_exercise_times_out() {
+    rustlings()
+        .args(["run", "infiniteLoop"])
+        .current_dir("tests/fixture/limits")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("timed out after 1 s"));
+}
+
+#[test]
+fn timeout_stops_what_cargo_runs() {
+    let start = std::time::Instant::now();
+    rustlings()
+        .args(["run", "sleepyTest"])
+        .current_dir("tests/fixture/limits")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("timed out after 2 s"));
+    assert!(start.elapsed() < std::time::Duration::from_secs(12));
+}
+
+#[test]
+fn verify_json_emits_events() {
+    let output = rustlings()
+        .args(["--format", "json", "verify"])
+        .current_dir("tests/fixture/success")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let events: Vec<serde_json::Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let passed: Vec<&str> = events
+        .iter()
+        .filter(|event| event["event"] == "passed")
+        .map(|event| event["exercise"].as_str().unwrap())
+        .collect();
+    assert_eq!(passed, ["compSuccess", "testSuccess"]);
+}
+
+#[test]
+fn started_events_are_emitted_when_grading_begins() {
+    let output = rustlings()
+        .args(["--format", "json", "verify", "--jobs", "2"])
+        .current_dir("tests/fixture/limits")
+        .output()
+        .unwrap();
+    let events: Vec<serde_json::Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let position = |kind: &str, exercise: &str| {
+        events
+            .iter()
+            .position(|event| event["event"] == kind && event["exercise"] == exercise)
+            .unwrap()
+    };
+    // The second exercise is graded while the first one runs into its timeout
+    assert!(position("started", "noisyOutput") < position("failed", "infiniteLoop"));
+}
+
+#[test]
+fn run_rustlings_list_json() {
+    let state = state_file();
+    rustlings_with_state(&state)
+        .args(["run", "finished_exercise"])
+        .current_dir("tests/fixture/state")
+        .assert()
+        .success();
+    rustlings_with_state(&state)
+        .args(["--format", "json", "list"])
+        .current_dir("tests/fixture/state")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            r#"{"event":"progress","done":1,"total":3}"#,
+        ));
+}
+
+#[test]
+fn verify_writes_reports() {
+    let dir = temp_dir("reports");
+    let junit = dir.join("junit.xml");
+    let tap = dir.join("results.tap");
+    rustlings()
+        .arg("verify")
+        .arg("--report")
+        .arg(format!("junit={}", junit.display()))
+        .arg("--report")
+        .arg(format!("tap={}", tap.display()))
+        .current_dir("tests/fixture/success")
+        .assert()
+        .success();
+
+    let junit = std::fs::read_to_string(junit).unwrap();
+    assert!(junit.contains(r#"<testsuites name="rustlings" tests="2" failures="0""#));
+    assert!(junit.contains(r#"<testcase name="testSuccess""#));
+    let tap = std::fs::read_to_string(tap).unwrap();
+    assert!(tap.contains("ok 1 - exercises/compSuccess\n"));
+    assert!(tap.contains("ok 2 - exercises/testSuccess\n"));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn reports_include_exercises_graded_after_a_failure() {
+    let dir = temp_dir("failure_reports");
+    let tap = dir.join("results.tap");
+    rustlings()
+        .args(["verify", "--jobs", "2"])
+        .arg("--report")
+        .arg(format!("tap={}", tap.display()))
+        .current_dir("tests/fixture/failure")
+        .assert()
+        .code(1);
+
+    let tap = std::fs::read_to_string(tap).unwrap();
+    assert!(tap.contains("not ok 1 - exercises/compFailure\n"), "{tap}");
+    assert!(tap.contains("not ok 2 - exercises/testFailure\n"), "{tap}");
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn unverified_exercise_is_not_done() {
+    rustlings()
+        .args(["list", "--solved"])
+        .current_dir("tests/fixture/state")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("finished_exercise").not());
+}
+
+#[test]
+fn verify_skips_unchanged_exercises() {
+    let state = state_file();
+    rustlings_with_state(&state)
+        .arg("verify")
+        .current_dir("tests/fixture/success")
+        .assert()
+        .success();
+    rustlings_with_state(&state)
+        .args(["--format", "json", "verify"])
+        .current_dir("tests/fixture/success")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            r#"{"event":"skipped","exercise":"compSuccess","mode":"compile"}"#,
+        ))
+        .stdout(predicates::str::contains(r#""event":"passed""#).not());
+
+    let state: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&state).unwrap()).unwrap();
+    assert_eq!(state["exercises"]["compSuccess"]["passed"], true);
+    assert_eq!(state["exercises"]["compSuccess"]["attempts"], 1);
+}
+
+// A copy of the given fixture, in which exercises can be changed.
+// It is removed when the test finishes.
+struct Course {
+    dir: PathBuf,
+}
+
+impl Course {
+    fn new(fixture: &str) -> Course {
+        let dir = temp_dir(fixture);
+        let mut dirs = vec![PathBuf::new()];
+        while let Some(relative) = dirs.pop() {
+            std::fs::create_dir_all(dir.join(&relative)).unwrap();
+            let source = Path::new("tests/fixture").join(fixture).join(&relative);
+            for entry in std::fs::read_dir(source).unwrap() {
+                let entry = entry.unwrap();
+                let path = relative.join(entry.file_name());
+                // What rustlings keeps in the fixture when run there
+                if path.starts_with(".rustlings") || path.starts_with(".rustlings-state.json") {
+                    continue;
+                }
+                if entry.file_type().unwrap().is_dir() {
+                    dirs.push(path);
+                } else {
+                    std::fs::copy(entry.path(), dir.join(path)).unwrap();
+                }
+            }
+        }
+        Course { dir }
+    }
+
+    // rustlings, running in the copy and keeping its data there like it
+    // does for learners
+    fn rustlings(&self) -> Command {
+        let mut cmd = rustlings();
+        cmd.current_dir(&self.dir).env_remove("RUSTLINGS_DATA_DIR");
+        cmd
+    }
+}
+
+impl Drop for Course {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[test]
+fn reset_restores_original_and_undo_restores_changes() {
+    let course = Course::new("reset");
+    let exercise = course.dir.join("exercises/basics/basics1.rs");
+    let original = std::fs::read_to_string(&exercise).unwrap();
+    // The original versions are recorded the first time rustlings runs
+    course
+        .rustlings()
+        .arg("list")
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("isn't a git repository"));
+
+    std::fs::write(&exercise, "fn main() {}\n").unwrap();
+    course
+        .rustlings()
+        .args(["reset", "basics1"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Reset exercises/basics/basics1.rs"));
+    assert_eq!(std::fs::read_to_string(&exercise).unwrap(), original);
+
+    course
+        .rustlings()
+        .args(["reset", "--undo"])
+        .assert()
+        .success();
+    assert_eq!(std::fs::read_to_string(&exercise).unwrap(), "fn main() {}\n");
+    course
+        .rustlings()
+        .args(["reset", "--undo"])
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn originals_come_from_the_first_commit() {
+    let course = Course::new("reset");
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .args(["-c", "user.name=rustlings", "-c", "user.email=rustlings@example.com"])
+            .args(args)
+            .current_dir(&course.dir)
+            .assert()
+            .success();
+    };
+    let exercise = course.dir.join("exercises/basics/basics1.rs");
+    let original = std::fs::read_to_string(&exercise).unwrap();
+    git(&["init", "--quiet"]);
+    git(&["add", "."]);
+    git(&["commit", "--quiet", "-m", "Hand out the course"]);
+    std::fs::write(&exercise, "fn main() {}\n").unwrap();
+    git(&["commit", "--quiet", "-am", "Solve basics1"]);
+
+    course
+        .rustlings()
+        .args(["reset", "basics1"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Reset exercises/basics/basics1.rs"))
+        .stderr(predicates::str::contains("isn't a git repository").not());
+    assert_eq!(std::fs::read_to_string(&exercise).unwrap(), original);
+    assert!(!course.dir.join(".rustlings/pristine").exists());
+}
+
+#[test]
+fn reset_topic() {
+    let course = Course::new("reset");
+    course.rustlings().arg("list").assert().success();
+    let exercises = ["exercises/basics/basics1.rs", "exercises/basics/basics2.rs"];
+    for exercise in exercises {
+        std::fs::write(course.dir.join(exercise), "").unwrap();
+    }
+    course
+        .rustlings()
+        .args(["reset", "basics"])
+        .assert()
+        .success();
+    for exercise in exercises {
+        assert_eq!(
+            std::fs::read(course.dir.join(exercise)).unwrap(),
+            std::fs::read(Path::new("tests/fixture/reset").join(exercise)).unwrap()
+        );
+    }
+}
+
+#[test]
+fn diff_shows_changes_against_original() {
+    let course = Course::new("reset");
+    course.rustlings().arg("list").assert().success();
+    let exercise = course.dir.join("exercises/basics/basics1.rs");
+    let changed = std::fs::read_to_string(&exercise)
+        .unwrap()
+        .replace("// I AM NOT DONE\n", "")
+        .replace("let x = 5;", "let x: i32 = 5;");
+    std::fs::write(&exercise, changed).unwrap();
+
+    course
+        .rustlings()
+        .args(["diff", "basics1"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "--- a/exercises/basics/basics1.rs (original)\n+++ b/exercises/basics/basics1.rs\n",
+        ))
+        .stdout(predicates::str::contains("-// I AM NOT DONE\n"))
+        .stdout(predicates::str::contains("+    let x: i32 = 5;\n"));
+    course
+        .rustlings()
+        .args(["diff", "--all", "--stat"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "basics: 1 of 2 exercise(s) changed, +1 -2\n",
+        ))
+        .stdout(predicates::str::contains("basics1").and(predicates::str::contains("basics2 ").not()));
+}
+
+#[test]
+fn modified_tests_are_detected() {
+    let course = Course::new("reset");
+    course.rustlings().arg("list").assert().success();
+    let exercise = course.dir.join("exercises/testing/testing1.rs");
+    let weakened = std::fs::read_to_string(&exercise)
+        .unwrap()
+        .replace("assert_eq!(double(2), 4);", "assert_eq!(6, 6);");
+    std::fs::write(&exercise, weakened).unwrap();
+
+    course
+        .rustlings()
+        .args(["run", "testing1"])
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains(
+            "The tests of exercises/testing/testing1.rs were modified (mod tests)!",
+        ));
+}
+
+#[test]
+fn cicvverify_reports_modified_tests() {
+    // Nothing was recorded in this course, like in CI,
+    // so the original versions come from the fixture
+    let course = Course::new("reset");
+    std::fs::create_dir_all(course.dir.join(".github/result")).unwrap();
+    let exercise = course.dir.join("exercises/testing/testing1.rs");
+    let source = std::fs::read_to_string(&exercise).unwrap();
+    let weakened = &source[..source.find("#[cfg(test)]").unwrap()];
+    std::fs::write(&exercise, weakened).unwrap();
+
+    let originals = std::fs::canonicalize("tests/fixture/reset").unwrap();
+    course
+        .rustlings()
+        .arg("cicvverify")
+        .arg("--pristine")
+        .arg(originals)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("testing1测试被修改"));
+
+    let result: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(course.dir.join(".github/result/check_result.json")).unwrap(),
+    )
+    .unwrap();
+    let testing1 = result["exercises"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["name"] == "testing1")
+        .unwrap();
+    assert_eq!(testing1["result"], false);
+    assert_eq!(testing1["status"], "tests_modified");
+    assert_eq!(result["statistics"]["total_tests_modified"], 1);
+}
+
+#[test]
+fn cicvverify_grades_without_the_original_exercises() {
+    let course = Course::new("reset");
+    std::fs::create_dir_all(course.dir.join(".github/result")).unwrap();
+    course
+        .rustlings()
+        .arg("cicvverify")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("modified tests can't be detected"));
+    // The exercises as submitted are never taken for the original ones
+    assert!(!course.dir.join(".rustlings/pristine").exists());
+
+    let originals = std::fs::canonicalize("tests/fixture/reset").unwrap();
+    course
+        .rustlings()
+        .arg("cicvverify")
+        .arg("--pristine")
+        .arg(originals)
+        .assert()
+        .success();
+    assert!(!course.dir.join(".rustlings/pristine").exists());
+}
+
+#[test]
+fn cicvverify_json_only_writes_events() {
+    let course = Course::new("reset");
+    std::fs::create_dir_all(course.dir.join(".github/result")).unwrap();
+    let output = course
+        .rustlings()
+        .args(["--format", "json", "cicvverify"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let events: Vec<serde_json::Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert!(events.iter().any(|event| event["event"] == "progress"));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("modified tests can't be detected"));
+}
+
+#[test]
+fn exercises_without_an_original_are_not_taken_for_modified() {
+    let course = Course::new("reset");
+    std::fs::create_dir_all(course.dir.join(".github/result")).unwrap();
+    let exercise = course.dir.join("exercises/testing/testing1.rs");
+    let source = std::fs::read_to_string(&exercise).unwrap();
+    std::fs::write(&exercise, &source[..source.find("#[cfg(test)]").unwrap()]).unwrap();
+    let empty = course.dir.join("empty");
+    std::fs::create_dir_all(&empty).unwrap();
+    course
+        .rustlings()
+        .arg("cicvverify")
+        .arg("--pristine")
+        .arg(&empty)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("testing1执行失败"))
+        .stdout(predicates::str::contains("测试被修改").not());
+}
+
+#[test]
+fn cicvverify_reports_exceeded_limits() {
+    let course = Course::new("limits");
+    std::fs::create_dir_all(course.dir.join(".github/result")).unwrap();
+    course
+        .rustlings()
+        .arg("cicvverify")
+        .arg("--pristine")
+        .arg(std::fs::canonicalize("tests/fixture/limits").unwrap())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("infiniteLoop超出资源限制: timed out after 1 s"));
+
+    let result: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(course.dir.join(".github/result/check_result.json")).unwrap(),
+    )
+    .unwrap();
+    let infinite_loop = result["exercises"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["name"] == "infiniteLoop")
+        .unwrap();
+    assert_eq!(infinite_loop["status"], "limit_exceeded");
+    assert_eq!(result["statistics"]["total_limit_exceeded"], 3);
+}
+
+#[test]
+fn cicvverify_runs_hidden_tests() {
+    let course = Course::new("reset");
+    // Learners don't get the hidden tests
+    std::fs::remove_dir_all(course.dir.join("hidden")).unwrap();
+    std::fs::create_dir_all(course.dir.join(".github/result")).unwrap();
+    // Passes the visible tests only
+    let exercise = course.dir.join("exercises/testing/testing1.rs");
+    let hard_coded = std::fs::read_to_string(&exercise)
+        .unwrap()
+        .replace("    x * 3", "    if x == 2 { 4 } else { 0 }");
+    std::fs::write(&exercise, hard_coded).unwrap();
+
+    let fixture = std::fs::canonicalize("tests/fixture/reset").unwrap();
+    course
+        .rustlings()
+        .arg("cicvverify")
+        .arg("--pristine")
+        .arg(&fixture)
+        .arg("--hidden-tests")
+        .arg(&fixture)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "testing1可见测试通过: 1/1, 隐藏测试通过: 0/2",
+        ));
+
+    let result: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(course.dir.join(".github/result/check_result.json")).unwrap(),
+    )
+    .unwrap();
+    let testing1 = result["exercises"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["name"] == "testing1")
+        .unwrap();
+    assert_eq!(testing1["result"], false);
+    assert_eq!(testing1["tests"]["visible"]["passed"], 1);
+    assert_eq!(testing1["tests"]["hidden"]["failed"], 2);
+
+    // Without the hidden tests, the exercise can't pass
+    course
+        .rustlings()
+        .arg("cicvverify")
+        .arg("--pristine")
+        .arg(&fixture)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("testing1执行失败"));
+}
+
+#[test]
+fn property_failures_are_reproducible_with_the_seed() {
+    let output = |seed: &str| {
+        let output = rustlings()
+            .args(["run", "sort1", "--seed", seed])
+            .current_dir("tests/fixture/properties")
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(
+            stdout.contains(&format!("run `rustlings run sort1 --seed {seed}`")),
+            "{stdout}"
+        );
+        stdout
+            .lines()
+            .find(|line| line.contains("the property doesn't hold"))
+            .map(str::to_string)
+            .unwrap()
+    };
+    let failure = output("42");
+    // Shrunk to three elements, since sorting the first two fixes any shorter input
+    assert!(failure.contains("hold for [0, 1, 0]"), "{failure}");
+    assert_eq!(output("42"), failure);
+}
+
+#[test]
+fn bench_reports_timings() {
+    rustlings()
+        .args(["run", "fast_sort"])
+        .current_dir("tests/fixture/bench")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("the bound is n log n"))
+        .stdout(predicates::str::contains("n =     8000:"));
+}
+
+#[test]
+fn bench_fails_solutions_growing_faster_than_the_bound() {
+    rustlings()
+        .args(["run", "slow_sort"])
+        .current_dir("tests/fixture/bench")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("too slow"));
+}
+
+#[test]
+fn bench_timings_are_in_json_output() {
+    let output = rustlings()
+        .args(["--format", "json", "run", "slow_sort"])
+        .current_dir("tests/fixture/bench")
+        .output()
+        .unwrap();
+    let events: Vec<serde_json::Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let benchmarked = events.iter().find(|e| e["event"] == "benchmarked").unwrap();
+    assert_eq!(benchmarked["bound"], "n log n");
+    assert_eq!(benchmarked["within_bound"], false);
+    assert_eq!(benchmarked["timings"].as_array().unwrap().len(), 4);
+    let failed = events.iter().find(|e| e["event"] == "failed").unwrap();
+    assert_eq!(failed["reason"], "too_slow");
+}
+
+#[test]
+fn leaked_allocations_fail_the_test() {
+    rustlings()
+        .args(["run", "leaking_list"])
+        .current_dir("tests/fixture/allocations")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains(
+            "sums leaked memory: 2 allocation(s) of 32 bytes in total were never freed",
+        ));
+}
+
+#[test]
+fn freeing_every_allocation_passes() {
+    rustlings()
+        .args(["run", "freeing_list"])
+        .current_dir("tests/fixture/allocations")
+        .assert()
+        .success();
+}
+
+#[test]
+fn double_frees_are_reported() {
+    rustlings()
+        .args(["run", "double_free"])
+        .current_dir("tests/fixture/allocations")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("was freed twice in test sums!"));
+}
+
+// A directory with a `cargo` that always fails, to put first in PATH so
+// that `cargo miri` can't be run
+#[cfg(unix)]
+fn without_cargo() -> std::ffi::OsString {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = temp_dir("no_cargo");
+    std::fs::create_dir_all(&dir).unwrap();
+    let cargo = dir.join("cargo");
+    std::fs::write(&cargo, "#!/bin/sh\nexit 1\n").unwrap();
+    std::fs::set_permissions(&cargo, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let mut paths = vec![dir];
+    paths.extend(std::env::split_paths(&std::env::var_os("PATH").unwrap_or_default()));
+    std::env::join_paths(paths).unwrap()
+}
+
+#[cfg(unix)]
+#[test]
+fn miri_exercises_are_skipped_without_miri() {
+    rustlings()
+        .args(["run", "miri1"])
+        .env("PATH", without_cargo())
+        .current_dir("tests/fixture/miri")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains(
+            "Skipped exercises/miri1.rs: Miri is not installed",
+        ));
+}
+
+#[cfg(unix)]
+#[test]
+fn skipped_miri_exercises_dont_hold_up_verify() {
+    let dir = temp_dir("miri");
+    let junit = dir.join("junit.xml");
+    rustlings()
+        .arg("verify")
+        .arg("--report")
+        .arg(format!("junit={}", junit.display()))
+        .env("PATH", without_cargo())
+        .current_dir("tests/fixture/miri")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Skipped exercises/miri1.rs"));
+    let junit = std::fs::read_to_string(junit).unwrap();
+    assert!(junit.contains(r#"<skipped message="Miri is not installed"#), "{junit}");
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn expected_output_passes() {
+    rustlings()
+        .args(["run", "greet"])
+        .current_dir("tests/fixture/output")
+        .assert()
+        .success();
+}
+
+#[test]
+fn unexpected_output_is_shown_as_a_diff() {
+    rustlings()
+        .args(["run", "count"])
+        .current_dir("tests/fixture/output")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("didn't print the expected output"))
+        .stdout(predicates::str::contains("+4"));
+}
+
+#[test]
+fn exercises_read_the_declared_stdin() {
+    rustlings()
+        .args(["run", "sum"])
+        .current_dir("tests/fixture/output")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("7"));
+}
+
+#[test]
+fn verify_checks_expected_output() {
+    rustlings()
+        .arg("verify")
+        .current_dir("tests/fixture/output")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("Ran exercises/count.rs, but it didn't print"));
+}
+
+#[test]
+fn compile_fail_passes_with_the_expected_errors() {
+    rustlings()
+        .args(["run", "moved"])
+        .current_dir("tests/fixture/compile_fail")
+        .assert()
+        .success();
+}
+
+#[test]
+fn compile_fail_rejects_other_errors() {
+    rustlings()
+        .args(["run", "mismatched"])
+        .current_dir("tests/fixture/compile_fail")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("failed to compile with E0308"))
+        .stdout(predicates::str::contains("must fail with E0382"));
+}
+
+#[test]
+fn compile_fail_rejects_compiling_code() {
+    rustlings()
+        .args(["run", "compiles"])
+        .current_dir("tests/fixture/compile_fail")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("compiled, but it must fail to compile"));
+}
+
+#[test]
+fn compile_fail_events_report_the_errors() {
+    rustlings()
+        .args(["--format", "json", "run", "moved"])
+        .current_dir("tests/fixture/compile_fail")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(r#""mode":"compile_fail""#))
+        .stdout(predicates::str::contains(r#""code":"E0382""#));
+}
+
+#[test]
+fn doctests_pass() {
+    rustlings()
+        .args(["run", "docs_pass"])
+        .current_dir("tests/fixture/doctest")
+        .assert()
+        .success();
+}
+
+#[test]
+fn doctest_failures_are_reported_individually() {
+    rustlings()
+        .args(["run", "docs_fail"])
+        .current_dir("tests/fixture/doctest")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("docs_fail.rs - double (line 3) ... FAILED"))
+        .stdout(predicates::str::contains("docs_fail.rs - triple (line 12) ... ok"));
+}
+
+#[test]
+fn doctests_are_compiled_with_the_rustc_flags() {
+    rustlings()
+        .args(["run", "docs_flags"])
+        .current_dir("tests/fixture/doctest")
+        .assert()
+        .success();
+}
+
+#[test]
+fn fmt_passes_when_formatted() {
+    rustlings()
+        .args(["run", "formatted"])
+        .current_dir("tests/fixture/fmt")
+        .assert()
+        .success();
+}
+
+#[test]
+fn fmt_shows_the_rustfmt_diff() {
+    rustlings()
+        .args(["run", "unformatted"])
+        .current_dir("tests/fixture/fmt")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("isn't formatted the way rustfmt formats it"))
+        .stdout(predicates::str::contains("+    let x = 1;"));
+}
+
+#[test]
+fn check_fmt_applies_to_other_modes() {
+    rustlings()
+        .args(["run", "checked"])
+        .current_dir("tests/fixture/fmt")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("isn't formatted the way rustfmt formats it"));
+}
+
+#[test]
+fn run_directory_exercise() {
+    rustlings()
+        .args(["run", "garden"])
+        .current_dir("tests/fixture/modules")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("I'm growing Asparagus!"));
+}
+
+#[test]
+fn test_directory_exercise() {
+    rustlings()
+        .args(["run", "shapes"])
+        .current_dir("tests/fixture/modules")
+        .assert()
+        .success();
+}
+
+#[test]
+fn directory_exercise_is_pending_until_every_file_is_done() {
+    rustlings()
+        .arg("verify")
+        .current_dir("tests/fixture/modules")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("I AM NOT DONE"));
+}
+
+#[test]
+fn reset_and_diff_cover_every_file_of_a_directory_exercise() {
+    let course = Course::new("modules");
+    course.rustlings().arg("list").assert().success();
+    let submodule = course.dir.join("exercises/garden/garden/vegetables.rs");
+    let original = std::fs::read_to_string(&submodule).unwrap();
+    std::fs::write(&submodule, original.replace("// I AM NOT DONE\n", "")).unwrap();
+
+    course
+        .rustlings()
+        .args(["diff", "garden"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "+++ b/exercises/garden/garden/vegetables.rs\n",
+        ))
+        .stdout(predicates::str::contains("-// I AM NOT DONE\n"));
+    course
+        .rustlings()
+        .args(["reset", "garden"])
+        .assert()
+        .success();
+    assert_eq!(std::fs::read_to_string(&submodule).unwrap(), original);
+}
+
+#[test]
+fn files_added_to_a_directory_exercise_are_shown_and_reset() {
+    let course = Course::new("modules");
+    course.rustlings().arg("list").assert().success();
+    let added = course.dir.join("exercises/garden/garden/fruits.rs");
+    std::fs::write(&added, "pub fn grow() {}\n").unwrap();
+    // Running the exercise again doesn't take the new file for an original
+    course.rustlings().args(["run", "garden"]).assert().success();
+
+    course
+        .rustlings()
+        .args(["diff", "garden"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("--- /dev/null\n"))
+        .stdout(predicates::str::contains(
+            "+++ b/exercises/garden/garden/fruits.rs\n",
+        ))
+        .stdout(predicates::str::contains("+pub fn grow() {}\n"));
+    course
+        .rustlings()
+        .args(["diff", "--all", "--stat"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("garden                   +1 -0"));
+
+    course
+        .rustlings()
+        .args(["reset", "garden"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Reset"));
+    assert!(!added.exists());
+    course
+        .rustlings()
+        .args(["reset", "--undo"])
+        .assert()
+        .success();
+    assert_eq!(std::fs::read_to_string(&added).unwrap(), "pub fn grow() {}\n");
+}
+
+#[test]
+fn cargo_exercise_enables_features() {
+    rustlings()
+        .args(["run", "features"])
+        .current_dir("tests/fixture/cargo")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("HELLO!"));
+}
+
+#[test]
+fn cargo_exercise_runs_build_script() {
+    rustlings()
+        .args(["run", "cfgs"])
+        .current_dir("tests/fixture/cargo")
+        .assert()
+        .success();
+}
+
+#[test]
+fn cargo_exercise_lints_with_clippy() {
+    rustlings()
+        .args(["run", "lint"])
+        .current_dir("tests/fixture/cargo")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("float_cmp"));
+}
+
+#[test]
+fn cargo_exercise_reports_compile_errors() {
+    rustlings()
+        .args(["run", "broken"])
+        .current_dir("tests/fixture/cargo")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("E0308"));
+}
+
+#[test]
+fn run_exercise_with_vendored_dependencies() {
+    rustlings()
+        .args(["run", "greet"])
+        .current_dir("tests/fixture/vendored")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("HELLO, FERRIS!"));
+}
+
+#[test]
+fn test_exercise_with_vendored_dependencies() {
+    rustlings()
+        .args(["run", "greet_test"])
+        .current_dir("tests/fixture/vendored")
+        .assert()
+        .success();
+}
+
+#[test]
+fn lsp_includes_vendored_dependencies() {
+    let course = Course::new("vendored");
+    course.rustlings().arg("lsp").assert().success();
+    let project: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(course.dir.join("rust-project.json")).unwrap()).unwrap();
+    let crates = project["crates"].as_array().unwrap();
+    let greet = crates
+        .iter()
+        .find(|krate| krate["root_module"] == "exercises/greet.rs")
+        .unwrap();
+    assert_eq!(greet["deps"][0]["name"], "greeting");
+    let greeting = &crates[greet["deps"][0]["crate"].as_u64().unwrap() as usize];
+    assert_eq!(greeting["root_module"], "vendor/greeting/src/lib.rs");
+    assert_eq!(greeting["deps"][0]["name"], "shout");
+}
+
+#[test]
+fn proc_macro_exercise_tests_the_macros() {
+    rustlings()
+        .args(["run", "describe"])
+        .current_dir("tests/fixture/proc_macro")
+        .assert()
+        .success();
+}
+
+#[test]
+fn proc_macro_exercise_reports_failing_tests() {
+    rustlings()
+        .args(["run", "log_calls"])
+        .current_dir("tests/fixture/proc_macro")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("logs_calls_in_order"));
+}
+
+#[test]
+fn proc_macro_exercise_reports_errors_in_expanded_code() {
+    rustlings()
+        .args(["run", "broken"])
+        .current_dir("tests/fixture/proc_macro")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("E0046"));
+}
+
+#[test]
+fn custom_exercise_passes_when_its_command_succeeds() {
+    rustlings()
+        .args(["run", "checked"])
+        .current_dir("tests/fixture/custom")
+        .assert()
+        .success();
+}
+
+#[test]
+fn custom_exercise_fails_when_its_command_fails() {
+    rustlings()
+        .args(["run", "broken"])
+        .current_dir("tests/fixture/custom")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("E0308"));
+}
+
+#[test]
+fn custom_exercise_without_command_fails() {
+    rustlings()
+        .args(["run", "commandless"])
+        .current_dir("tests/fixture/custom")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("No command to check"));
+}
+
+#[test]
+fn exercise_compiles_with_its_edition() {
+    rustlings()
+        .args(["run", "edition"])
+        .current_dir("tests/fixture/flags")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Chained!"));
+}
+
+#[test]
+fn exercise_compiles_with_its_rustc_flags() {
+    rustlings()
+        .args(["run", "denied"])
+        .current_dir("tests/fixture/flags")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("function `unused` is never used"));
+}
+
+#[test]
+fn exercise_compiles_with_its_cfg() {
+    rustlings()
+        .args(["run", "configured"])
+        .current_dir("tests/fixture/flags")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("HELLO!"));
+}
+
+#[test]
+fn cargo_exercise_compiles_with_its_cfg() {
+    rustlings()
+        .args(["run", "configured_cargo"])
+        .current_dir("tests/fixture/flags")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("HELLO!"));
+}
+
+#[test]
+fn lsp_configures_crates_like_their_exercises() {
+    let course = Course::new("flags");
+    course.rustlings().arg("lsp").assert().success();
+    let project: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(course.dir.join("rust-project.json")).unwrap()).unwrap();
+    let crates = project["crates"].as_array().unwrap();
+    let find = |root: &str| crates.iter().find(|krate| krate["root_module"] == root).unwrap();
+    assert_eq!(find("exercises/edition.rs")["edition"], "2024");
+    assert_eq!(find("exercises/configured.rs")["cfg"], serde_json::json!(["test", "shout"]));
+}